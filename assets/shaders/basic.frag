@@ -3,43 +3,119 @@
 in vec3 v_normal;
 in vec3 v_world_pos;
 in vec2 v_tex;
+in vec4 v_light_space_pos;
+in vec3 v_tint;
 
+// gradient u_color1 -> u_color2; t = dot(N, u_gradient_normal) * 0.5 + 0.5
+// + dot(pozycja w świecie, u_gradient_world), np. podłoże: normal 0, world (0.1, 0, 0)
 uniform vec3 u_color1;
 uniform vec3 u_color2;
-uniform int u_is_ground;
+uniform vec3 u_gradient_normal;
+uniform vec3 u_gradient_world;
 
-// tekstury
+// tekstury (materiały bez tekstury dostają biały teksel)
 uniform sampler2D u_diffuse;
-uniform int u_use_texture;     // 1 = używamy tekstury, 0 = gradient
 uniform int u_alpha_cutout;    // 1 = używamy alpha discard
+uniform int u_alpha_blend;     // 1 = alfa z tekstury * u_opacity, inaczej 1
+uniform float u_opacity;
+
+// cienie od jednego światła kierunkowego
+uniform sampler2DShadow u_shadow_map;
+uniform int u_shadow_light;    // indeks światła rzucającego cień, -1 = brak
+uniform float u_shadow_bias;
+
+// światła – układ zgodny z LightBlock w src/light.rs
+#define MAX_LIGHTS 32
+#define LIGHT_DIRECTIONAL 0
+#define LIGHT_POINT 1
+#define LIGHT_SPOT 2
+
+struct Light {
+    vec4 position;   // xyz pozycja, w rodzaj światła
+    vec4 direction;  // xyz kierunek, w zasięg
+    vec4 color;      // rgb kolor, w natężenie
+    vec4 cone;       // x cos(inner), y cos(outer)
+};
+
+layout(std140) uniform Lights {
+    vec4 u_ambient;
+    int u_light_count;
+    Light u_lights[MAX_LIGHTS];
+};
 
 out vec4 FragColor;
 
-void main() {
-    vec3 N = normalize(v_normal);
-    vec3 light_dir = normalize(vec3(0.3, 1.0, 0.5));
-    float diff = max(dot(N, light_dir), 0.0);
+vec3 light_contribution(Light light, vec3 N) {
+    int kind = int(light.position.w);
+    vec3 L;
+    float attenuation = 1.0;
 
-    float t;
-    if (u_is_ground == 1) {
-        t = clamp(v_world_pos.x * 0.1 + 0.5, 0.0, 1.0);
+    if (kind == LIGHT_DIRECTIONAL) {
+        L = -normalize(light.direction.xyz);
     } else {
-        t = N.y * 0.5 + 0.5;
+        vec3 to_light = light.position.xyz - v_world_pos;
+        float dist = length(to_light);
+        L = to_light / max(dist, 0.0001);
+
+        // łagodne wygaszenie do zera na granicy zasięgu
+        float falloff = clamp(1.0 - pow(dist / light.direction.w, 4.0), 0.0, 1.0);
+        attenuation = falloff * falloff / (dist * dist + 1.0);
+
+        if (kind == LIGHT_SPOT) {
+            float cos_angle = dot(-L, normalize(light.direction.xyz));
+            attenuation *= smoothstep(light.cone.y, light.cone.x, cos_angle);
+        }
     }
 
-    vec3 base_color = mix(u_color1, u_color2, t);
+    float diff = max(dot(N, L), 0.0);
+    return light.color.rgb * light.color.w * diff * attenuation;
+}
+
+// 1.0 = oświetlone, 0.0 = w pełnym cieniu; PCF 3x3
+float shadow_factor(vec3 N, vec3 L) {
+    vec3 coords = v_light_space_pos.xyz / v_light_space_pos.w * 0.5 + 0.5;
+    if (coords.z > 1.0) {
+        return 1.0;
+    }
 
-    if (u_use_texture == 1) {
-        vec4 texColor = texture(u_diffuse, v_tex);
+    // większe przesunięcie na powierzchniach ustawionych skośnie do światła
+    float bias = max(u_shadow_bias * 2.0 * (1.0 - dot(N, L)), u_shadow_bias);
+    vec2 texel = 1.0 / vec2(textureSize(u_shadow_map, 0));
 
-        if (u_alpha_cutout == 1 && texColor.a < 0.5) {
-            discard;
+    float lit = 0.0;
+    for (int x = -1; x <= 1; x++) {
+        for (int y = -1; y <= 1; y++) {
+            vec2 offset = vec2(x, y) * texel;
+            lit += texture(u_shadow_map, vec3(coords.xy + offset, coords.z - bias));
         }
+    }
+    return lit / 9.0;
+}
+
+void main() {
+    vec3 N = normalize(v_normal);
 
-        // mnożenie tekstury przez gradient t
-        base_color = texColor.rgb * base_color;
+    float t = dot(N, u_gradient_normal) * 0.5 + 0.5 + dot(v_world_pos, u_gradient_world);
+    vec3 base_color = mix(u_color1, u_color2, clamp(t, 0.0, 1.0)) * v_tint;
+
+    vec4 texColor = texture(u_diffuse, v_tex);
+    if (u_alpha_cutout == 1 && texColor.a < 0.5) {
+        discard;
+    }
+
+    // mnożenie tekstury przez gradient t
+    base_color = texColor.rgb * base_color;
+
+    vec3 lighting = u_ambient.rgb;
+    for (int i = 0; i < u_light_count; i++) {
+        vec3 contribution = light_contribution(u_lights[i], N);
+        if (i == u_shadow_light) {
+            contribution *= shadow_factor(N, -normalize(u_lights[i].direction.xyz));
+        }
+        lighting += contribution;
     }
 
-    vec3 final_color = base_color * (0.3 + 0.7 * diff);
-    FragColor = vec4(final_color, 1.0);
+    vec3 final_color = base_color * lighting;
+    float alpha = u_alpha_blend == 1 ? texColor.a * u_opacity : 1.0;
+    FragColor = vec4(final_color, alpha);
 }
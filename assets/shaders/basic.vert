@@ -4,20 +4,28 @@ layout(location = 0) in vec3 a_pos;
 layout(location = 1) in vec3 a_normal;
 layout(location = 2) in vec2 a_tex;
 
-uniform mat4 u_model;
+// dane egzemplarza (src/instancing.rs)
+layout(location = 3) in mat4 a_model;
+layout(location = 7) in vec3 a_tint;
+
 uniform mat4 u_view;
 uniform mat4 u_proj;
+uniform mat4 u_light_space;
 
 out vec3 v_normal;
 out vec3 v_world_pos;
 out vec2 v_tex;
+out vec4 v_light_space_pos;
+out vec3 v_tint;
 
 void main() {
-    vec4 world_pos = u_model * vec4(a_pos, 1.0);
+    vec4 world_pos = a_model * vec4(a_pos, 1.0);
 
     v_world_pos = world_pos.xyz;
-    v_normal = mat3(u_model) * a_normal;
+    v_normal = mat3(a_model) * a_normal;
     v_tex = a_tex;
+    v_tint = a_tint;
+    v_light_space_pos = u_light_space * world_pos;
 
     gl_Position = u_proj * u_view * world_pos;
 }
@@ -0,0 +1,197 @@
+#version 330 core
+
+// PBR metallic-roughness (Cook-Torrance, GGX). Wierzchołki: basic.vert.
+
+in vec3 v_normal;
+in vec3 v_world_pos;
+in vec2 v_tex;
+in vec4 v_light_space_pos;
+in vec3 v_tint;
+
+uniform vec3 u_camera_pos;
+
+// współczynniki mnożone przez tekstury
+uniform vec3 u_albedo;
+uniform float u_metallic;
+uniform float u_roughness;
+uniform float u_ao;
+uniform vec3 u_emissive;
+
+// tekstury (brakujące: biały teksel, płaska mapa normalnych)
+uniform sampler2D u_diffuse;       // albedo (sRGB) + alfa
+uniform sampler2D u_normal_map;    // przestrzeń stycznej
+uniform sampler2D u_metallic_map;  // kanał R
+uniform sampler2D u_roughness_map; // kanał R
+uniform sampler2D u_ao_map;        // kanał R
+uniform sampler2D u_emissive_map;  // sRGB
+uniform int u_alpha_cutout;
+uniform int u_alpha_blend;
+uniform float u_opacity;
+
+// cienie – jak w basic.frag
+uniform sampler2DShadow u_shadow_map;
+uniform int u_shadow_light;
+uniform float u_shadow_bias;
+
+// światła – układ zgodny z LightBlock w src/light.rs
+#define MAX_LIGHTS 32
+#define LIGHT_DIRECTIONAL 0
+#define LIGHT_POINT 1
+#define LIGHT_SPOT 2
+
+struct Light {
+    vec4 position;   // xyz pozycja, w rodzaj światła
+    vec4 direction;  // xyz kierunek, w zasięg
+    vec4 color;      // rgb kolor, w natężenie
+    vec4 cone;       // x cos(inner), y cos(outer)
+};
+
+layout(std140) uniform Lights {
+    vec4 u_ambient;
+    int u_light_count;
+    Light u_lights[MAX_LIGHTS];
+};
+
+out vec4 FragColor;
+
+const float PI = 3.14159265359;
+
+vec3 srgb_to_linear(vec3 c) {
+    return pow(c, vec3(2.2));
+}
+
+// normalna z mapy; TBN z pochodnych ekranowych (siatki nie mają stycznych)
+vec3 surface_normal() {
+    vec3 N = normalize(v_normal);
+    vec3 tangent_normal = texture(u_normal_map, v_tex).xyz * 2.0 - 1.0;
+
+    vec3 dp1 = dFdx(v_world_pos);
+    vec3 dp2 = dFdy(v_world_pos);
+    vec2 duv1 = dFdx(v_tex);
+    vec2 duv2 = dFdy(v_tex);
+
+    vec3 dp2perp = cross(dp2, N);
+    vec3 dp1perp = cross(N, dp1);
+    vec3 T = dp2perp * duv1.x + dp1perp * duv2.x;
+    vec3 B = dp2perp * duv1.y + dp1perp * duv2.y;
+
+    float inv_max = inversesqrt(max(dot(T, T), dot(B, B)));
+    if (isinf(inv_max) || isnan(inv_max)) {
+        // brak współrzędnych tekstury – zostaje normalna wierzchołka
+        return N;
+    }
+    mat3 TBN = mat3(T * inv_max, B * inv_max, N);
+    return normalize(TBN * tangent_normal);
+}
+
+float distribution_ggx(float NdotH, float roughness) {
+    float a = roughness * roughness;
+    float a2 = a * a;
+    float d = NdotH * NdotH * (a2 - 1.0) + 1.0;
+    return a2 / (PI * d * d);
+}
+
+float geometry_smith(float NdotV, float NdotL, float roughness) {
+    float r = roughness + 1.0;
+    float k = r * r / 8.0;
+    float gv = NdotV / (NdotV * (1.0 - k) + k);
+    float gl = NdotL / (NdotL * (1.0 - k) + k);
+    return gv * gl;
+}
+
+vec3 fresnel_schlick(float cos_theta, vec3 F0) {
+    return F0 + (1.0 - F0) * pow(clamp(1.0 - cos_theta, 0.0, 1.0), 5.0);
+}
+
+// kierunek do światła i jego natężenie w tym punkcie
+vec3 light_radiance(Light light, out vec3 L) {
+    int kind = int(light.position.w);
+    float attenuation = 1.0;
+
+    if (kind == LIGHT_DIRECTIONAL) {
+        L = -normalize(light.direction.xyz);
+    } else {
+        vec3 to_light = light.position.xyz - v_world_pos;
+        float dist = length(to_light);
+        L = to_light / max(dist, 0.0001);
+
+        float falloff = clamp(1.0 - pow(dist / light.direction.w, 4.0), 0.0, 1.0);
+        attenuation = falloff * falloff / (dist * dist + 1.0);
+
+        if (kind == LIGHT_SPOT) {
+            float cos_angle = dot(-L, normalize(light.direction.xyz));
+            attenuation *= smoothstep(light.cone.y, light.cone.x, cos_angle);
+        }
+    }
+
+    return light.color.rgb * light.color.w * attenuation;
+}
+
+float shadow_factor(vec3 N, vec3 L) {
+    vec3 coords = v_light_space_pos.xyz / v_light_space_pos.w * 0.5 + 0.5;
+    if (coords.z > 1.0) {
+        return 1.0;
+    }
+
+    float bias = max(u_shadow_bias * 2.0 * (1.0 - dot(N, L)), u_shadow_bias);
+    vec2 texel = 1.0 / vec2(textureSize(u_shadow_map, 0));
+
+    float lit = 0.0;
+    for (int x = -1; x <= 1; x++) {
+        for (int y = -1; y <= 1; y++) {
+            vec2 offset = vec2(x, y) * texel;
+            lit += texture(u_shadow_map, vec3(coords.xy + offset, coords.z - bias));
+        }
+    }
+    return lit / 9.0;
+}
+
+void main() {
+    vec4 albedo_sample = texture(u_diffuse, v_tex);
+    if (u_alpha_cutout == 1 && albedo_sample.a < 0.5) {
+        discard;
+    }
+
+    vec3 albedo = srgb_to_linear(albedo_sample.rgb) * u_albedo * v_tint;
+    float metallic = clamp(texture(u_metallic_map, v_tex).r * u_metallic, 0.0, 1.0);
+    float roughness = clamp(texture(u_roughness_map, v_tex).r * u_roughness, 0.04, 1.0);
+    float ao = texture(u_ao_map, v_tex).r * u_ao;
+    vec3 emissive = srgb_to_linear(texture(u_emissive_map, v_tex).rgb) * u_emissive;
+
+    vec3 N = surface_normal();
+    vec3 V = normalize(u_camera_pos - v_world_pos);
+    float NdotV = max(dot(N, V), 0.0001);
+
+    // dielektryki odbijają ~4%, metale w kolorze albedo
+    vec3 F0 = mix(vec3(0.04), albedo, metallic);
+
+    vec3 Lo = vec3(0.0);
+    for (int i = 0; i < u_light_count; i++) {
+        vec3 L;
+        vec3 radiance = light_radiance(u_lights[i], L);
+        float NdotL = max(dot(N, L), 0.0);
+        if (NdotL <= 0.0) {
+            continue;
+        }
+        if (i == u_shadow_light) {
+            radiance *= shadow_factor(normalize(v_normal), L);
+        }
+
+        vec3 H = normalize(V + L);
+        float NDF = distribution_ggx(max(dot(N, H), 0.0), roughness);
+        float G = geometry_smith(NdotV, NdotL, roughness);
+        vec3 F = fresnel_schlick(max(dot(H, V), 0.0), F0);
+
+        vec3 specular = NDF * G * F / (4.0 * NdotV * NdotL + 0.0001);
+        vec3 kD = (vec3(1.0) - F) * (1.0 - metallic);
+
+        Lo += (kD * albedo / PI + specular) * radiance * NdotL;
+    }
+
+    vec3 ambient = u_ambient.rgb * albedo * ao;
+    vec3 color = ambient + Lo + emissive;
+
+    // wynik w sRGB, bez tone mappingu (jak widok "Standard" w Blenderze)
+    float alpha = u_alpha_blend == 1 ? albedo_sample.a * u_opacity : 1.0;
+    FragColor = vec4(pow(clamp(color, 0.0, 1.0), vec3(1.0 / 2.2)), alpha);
+}
@@ -0,0 +1,13 @@
+#version 330 core
+
+in vec2 v_tex;
+
+// tylko dla obiektów z alpha cutout (kwiatki) – reszta zapisuje samą głębokość
+uniform sampler2D u_diffuse;
+uniform int u_alpha_cutout;
+
+void main() {
+    if (u_alpha_cutout == 1 && texture(u_diffuse, v_tex).a < 0.5) {
+        discard;
+    }
+}
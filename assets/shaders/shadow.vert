@@ -0,0 +1,14 @@
+#version 330 core
+
+layout(location = 0) in vec3 a_pos;
+layout(location = 2) in vec2 a_tex;
+layout(location = 3) in mat4 a_model;
+
+uniform mat4 u_light_space;
+
+out vec2 v_tex;
+
+void main() {
+    v_tex = a_tex;
+    gl_Position = u_light_space * a_model * vec4(a_pos, 1.0);
+}
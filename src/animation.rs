@@ -0,0 +1,324 @@
+use cgmath::{InnerSpace, Quaternion, Vector3};
+use serde::{Deserialize, Serialize};
+
+use crate::components::Renderable;
+use crate::ecs::{Entity, World};
+use crate::scene_graph::Transform;
+
+/// Sposób przejścia między sąsiednimi klatkami kluczowymi
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Interpolation {
+    /// Wartość klatki trzymana aż do następnej
+    Step,
+    #[default]
+    Linear,
+    /// Catmull-Rom przez sąsiednie klatki; dla obrotu slerp z wygładzonym czasem
+    Cubic,
+}
+
+/// Krzywa czasu odcinka zaczynającego się w danej klatce
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Easing {
+    #[default]
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+}
+
+impl Easing {
+    pub fn apply(self, t: f32) -> f32 {
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum PlaybackMode {
+    #[default]
+    Loop,
+    /// Do końca i z powrotem
+    PingPong,
+    /// Raz, potem zatrzymanie na ostatniej klatce
+    Once,
+}
+
+/// Wartość, którą da się interpolować między klatkami
+pub trait Keyable: Copy {
+    fn lerp(a: Self, b: Self, t: f32) -> Self;
+    /// Odcinek `p1` -> `p2`, `p0` i `p3` to sąsiednie klatki
+    fn cubic(p0: Self, p1: Self, p2: Self, p3: Self, t: f32) -> Self;
+}
+
+impl Keyable for Vector3<f32> {
+    fn lerp(a: Self, b: Self, t: f32) -> Self {
+        a + (b - a) * t
+    }
+
+    fn cubic(p0: Self, p1: Self, p2: Self, p3: Self, t: f32) -> Self {
+        let t2 = t * t;
+        let t3 = t2 * t;
+        (p1 * 2.0
+            + (p2 - p0) * t
+            + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * t2
+            + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * t3)
+            * 0.5
+    }
+}
+
+impl Keyable for Quaternion<f32> {
+    fn lerp(a: Self, b: Self, t: f32) -> Self {
+        // krótsza droga po sferze
+        let b = if a.dot(b) < 0.0 { -b } else { b };
+        a.slerp(b, t).normalize()
+    }
+
+    fn cubic(_p0: Self, p1: Self, p2: Self, _p3: Self, t: f32) -> Self {
+        <Self as Keyable>::lerp(p1, p2, t * t * (3.0 - 2.0 * t))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Keyframe<T> {
+    pub time: f32,
+    pub value: T,
+    pub easing: Easing,
+}
+
+/// Klatki kluczowe jednej właściwości, posortowane po czasie
+#[derive(Debug, Clone, PartialEq)]
+pub struct Track<T> {
+    keys: Vec<Keyframe<T>>,
+    pub interpolation: Interpolation,
+}
+
+impl<T: Keyable> Track<T> {
+    pub fn new(mut keys: Vec<Keyframe<T>>, interpolation: Interpolation) -> Self {
+        keys.sort_by(|a, b| a.time.total_cmp(&b.time));
+        Track {
+            keys,
+            interpolation,
+        }
+    }
+
+    pub fn keys(&self) -> &[Keyframe<T>] {
+        &self.keys
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.keys.last().map_or(0.0, |key| key.time)
+    }
+
+    /// Wartość w chwili `time`; przed pierwszą i po ostatniej klatce – wartość skrajna
+    pub fn sample(&self, time: f32) -> Option<T> {
+        let keys = &self.keys;
+        let first = keys.first()?;
+        if time <= first.time {
+            return Some(first.value);
+        }
+        let next = keys.partition_point(|key| key.time <= time);
+        if next == keys.len() {
+            return keys.last().map(|key| key.value);
+        }
+
+        let (a, b) = (&keys[next - 1], &keys[next]);
+        let span = b.time - a.time;
+        let t = if span > 0.0 {
+            a.easing.apply((time - a.time) / span)
+        } else {
+            1.0
+        };
+
+        Some(match self.interpolation {
+            Interpolation::Step => a.value,
+            Interpolation::Linear => T::lerp(a.value, b.value, t),
+            Interpolation::Cubic => {
+                let before = keys[next.saturating_sub(2)].value;
+                let after = keys.get(next + 1).unwrap_or(b).value;
+                T::cubic(before, a.value, b.value, after, t)
+            }
+        })
+    }
+}
+
+/// Nazwany zestaw ścieżek odtwarzanych razem
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AnimationClip {
+    pub name: String,
+    pub translation: Option<Track<Vector3<f32>>>,
+    pub rotation: Option<Track<Quaternion<f32>>>,
+    pub scale: Option<Track<Vector3<f32>>>,
+    /// Mnożnik koloru (`Renderable::tint`)
+    pub color: Option<Track<Vector3<f32>>>,
+}
+
+impl AnimationClip {
+    pub fn duration(&self) -> f32 {
+        let vectors = [&self.translation, &self.scale, &self.color];
+        vectors
+            .into_iter()
+            .flatten()
+            .map(Track::duration)
+            .chain(self.rotation.as_ref().map(Track::duration))
+            .fold(0.0, f32::max)
+    }
+}
+
+/// Komponent odtwarzający klipy encji; `update` zapisuje wynik
+/// do jej `Transform` i `Renderable::tint`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Animator {
+    pub clips: Vec<AnimationClip>,
+    current: usize,
+    /// Czas od początku odtwarzania (przed zawinięciem wg `mode`)
+    time: f32,
+    pub playing: bool,
+    pub mode: PlaybackMode,
+    pub speed: f32,
+}
+
+impl Animator {
+    pub fn new(clips: Vec<AnimationClip>) -> Self {
+        Animator {
+            clips,
+            current: 0,
+            time: 0.0,
+            playing: true,
+            mode: PlaybackMode::Loop,
+            speed: 1.0,
+        }
+    }
+
+    pub fn with_mode(mut self, mode: PlaybackMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    pub fn with_playing(mut self, playing: bool) -> Self {
+        self.playing = playing;
+        self
+    }
+
+    pub fn current(&self) -> Option<&AnimationClip> {
+        self.clips.get(self.current)
+    }
+
+    pub fn current_index(&self) -> usize {
+        self.current
+    }
+
+    /// Odtwarza klip od początku
+    pub fn play(&mut self, clip: usize) {
+        if clip < self.clips.len() {
+            self.current = clip;
+            self.time = 0.0;
+            self.playing = true;
+        }
+    }
+
+    /// Wznawia bieżący klip; po końcu trybu `Once` zaczyna od nowa
+    pub fn resume(&mut self) {
+        let duration = self.current().map_or(0.0, AnimationClip::duration);
+        if self.mode == PlaybackMode::Once && self.time >= duration {
+            self.time = 0.0;
+        }
+        self.playing = true;
+    }
+
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    /// Przewija do `time` sekund klipu
+    pub fn seek(&mut self, time: f32) {
+        let duration = self.current().map_or(0.0, AnimationClip::duration);
+        self.time = time.clamp(0.0, duration);
+    }
+
+    pub fn advance(&mut self, dt: f32) {
+        if !self.playing {
+            return;
+        }
+        self.time += dt * self.speed;
+
+        let duration = self.current().map_or(0.0, AnimationClip::duration);
+        if self.mode == PlaybackMode::Once && self.time >= duration {
+            self.time = duration;
+            self.playing = false;
+        }
+    }
+
+    /// Czas w klipie po uwzględnieniu trybu odtwarzania
+    pub fn clip_time(&self) -> f32 {
+        let duration = self.current().map_or(0.0, AnimationClip::duration);
+        if duration <= 0.0 {
+            return 0.0;
+        }
+        match self.mode {
+            PlaybackMode::Loop => self.time.rem_euclid(duration),
+            PlaybackMode::PingPong => {
+                let t = self.time.rem_euclid(2.0 * duration);
+                if t > duration { 2.0 * duration - t } else { t }
+            }
+            PlaybackMode::Once => self.time.clamp(0.0, duration),
+        }
+    }
+}
+
+/// Przesuwa animatory o `dt` i zapisuje próbki do komponentów encji
+pub fn update(world: &mut World, dt: f32) {
+    let animated: Vec<Entity> = world
+        .query::<&Animator>()
+        .map(|(entity, _)| entity)
+        .collect();
+
+    for entity in animated {
+        let Some(animator) = world.get_mut::<Animator>(entity) else {
+            continue;
+        };
+        animator.advance(dt);
+        let time = animator.clip_time();
+        let Some(clip) = animator.current() else {
+            continue;
+        };
+
+        let translation = clip.translation.as_ref().and_then(|t| t.sample(time));
+        let rotation = clip.rotation.as_ref().and_then(|t| t.sample(time));
+        let scale = clip.scale.as_ref().and_then(|t| t.sample(time));
+        let color = clip.color.as_ref().and_then(|t| t.sample(time));
+
+        if (translation.is_some() || rotation.is_some() || scale.is_some())
+            && let Some(transform) = world.get_mut::<Transform>(entity)
+        {
+            if let Some(translation) = translation {
+                transform.translation = translation;
+            }
+            if let Some(rotation) = rotation {
+                transform.rotation = rotation;
+            }
+            if let Some(scale) = scale {
+                transform.scale = scale;
+            }
+        }
+        if let Some(color) = color
+            && let Some(renderable) = world.get_mut::<Renderable>(entity)
+        {
+            renderable.tint = color;
+        }
+    }
+}
@@ -0,0 +1,339 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use crate::mesh::{Mesh, ObjPart};
+use crate::shader::{Program, ShaderError};
+use crate::textures::{Texture, Wrap};
+
+/// Klucze wbudowanych zasobów w cache; `mesh`/`texture` zwracają
+/// pod nimi zasób wbudowany, więc scena może je wskazać jak plik
+pub const QUAD_KEY: &str = "<quad>";
+const PLACEHOLDER_MESH_KEY: &str = "<placeholder>";
+const CHECKERBOARD_KEY: &str = "<checkerboard>";
+const WHITE_KEY: &str = "<white>";
+const FLAT_NORMAL_KEY: &str = "<flat-normal>";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssetKind {
+    Mesh,
+    Model,
+    Texture,
+    Program,
+}
+
+/// Wpis na liście zasobów trzymanych w pamięci GPU
+#[derive(Debug, Clone)]
+pub struct ResidentAsset {
+    pub kind: AssetKind,
+    pub path: String,
+    /// Liczba uchwytów poza samym cache
+    pub users: usize,
+}
+
+/// Cache zasobów GPU po ścieżce. Zwraca współdzielone uchwyty (`Rc`),
+/// zasób jest zwalniany gdy zniknie z cache i z ostatniego obiektu.
+/// Gdy plik nie da się wczytać, błąd trafia do logu, a w jego miejsce
+/// zwracany jest zasób zastępczy (sześcian, szachownica, shader magenta) –
+/// osobny dla każdej ścieżki, więc zmiana parametrów jednego nie dotyka innych.
+#[derive(Default)]
+pub struct AssetManager {
+    meshes: HashMap<PathBuf, Rc<Mesh>>,
+    models: HashMap<PathBuf, Vec<ObjPart>>,
+    /// Osobna kopia dla każdego trybu zawijania tej samej ścieżki
+    textures: HashMap<(PathBuf, Wrap), Rc<Texture>>,
+    programs: HashMap<(PathBuf, PathBuf), Rc<Program>>,
+}
+
+impl AssetManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn mesh<P: AsRef<Path>>(&mut self, path: P) -> Rc<Mesh> {
+        let path = path.as_ref();
+        if let Some(mesh) = self.meshes.get(path) {
+            return mesh.clone();
+        }
+        match path.to_str() {
+            Some(QUAD_KEY) => return self.quad(),
+            Some(PLACEHOLDER_MESH_KEY) => return self.placeholder_mesh(),
+            _ => {}
+        }
+
+        let mesh = match Mesh::from_obj(path) {
+            Ok(mesh) => Rc::new(mesh),
+            Err(e) => {
+                eprintln!("{}, using placeholder mesh", e);
+                Rc::new(Mesh::placeholder())
+            }
+        };
+        self.meshes.insert(path.to_path_buf(), mesh.clone());
+        mesh
+    }
+
+    pub fn quad(&mut self) -> Rc<Mesh> {
+        self.meshes
+            .entry(PathBuf::from(QUAD_KEY))
+            .or_insert_with(|| Rc::new(Mesh::quad()))
+            .clone()
+    }
+
+    pub fn placeholder_mesh(&mut self) -> Rc<Mesh> {
+        self.meshes
+            .entry(PathBuf::from(PLACEHOLDER_MESH_KEY))
+            .or_insert_with(|| Rc::new(Mesh::placeholder()))
+            .clone()
+    }
+
+    /// Wszystkie części pliku OBJ razem z materiałami MTL
+    pub fn model<P: AsRef<Path>>(&mut self, path: P) -> Vec<ObjPart> {
+        let path = path.as_ref();
+        if let Some(parts) = self.models.get(path) {
+            return parts.clone();
+        }
+
+        let parts = match Mesh::from_obj_parts(path) {
+            Ok(parts) => parts,
+            Err(e) => {
+                eprintln!("{}, using placeholder mesh", e);
+                vec![ObjPart {
+                    name: PLACEHOLDER_MESH_KEY.to_string(),
+                    mesh: Rc::new(Mesh::placeholder()),
+                    material: None,
+                }]
+            }
+        };
+        self.models.insert(path.to_path_buf(), parts.clone());
+        parts
+    }
+
+    pub fn texture<P: AsRef<Path>>(&mut self, path: P) -> Rc<Texture> {
+        self.texture_with_wrap(path, Wrap::Repeat)
+    }
+
+    /// Tekstura z danym zawijaniem; obiekty proszące o inny tryb dostają
+    /// osobną kopię, więc jeden nie zmienia wyglądu drugiego
+    pub fn texture_with_wrap<P: AsRef<Path>>(&mut self, path: P, wrap: Wrap) -> Rc<Texture> {
+        let path = path.as_ref();
+        let key = (path.to_path_buf(), wrap);
+        if let Some(texture) = self.textures.get(&key) {
+            return texture.clone();
+        }
+        let builtin = match path.to_str() {
+            Some(CHECKERBOARD_KEY) => Some(self.checkerboard()),
+            Some(WHITE_KEY) => Some(self.white()),
+            Some(FLAT_NORMAL_KEY) => Some(self.flat_normal()),
+            _ => None,
+        };
+        if let Some(builtin) = builtin {
+            if wrap != Wrap::Repeat {
+                eprintln!("Builtin texture {:?} always uses {:?}", path, Wrap::Repeat);
+            }
+            return builtin;
+        }
+
+        let texture = match Texture::from_file(path) {
+            Ok(texture) => texture,
+            Err(e) => {
+                eprintln!("{}, using checkerboard", e);
+                Texture::checkerboard()
+            }
+        };
+        let texture = Rc::new(texture.with_source(path).with_wrap(wrap));
+        self.textures.insert(key, texture.clone());
+        texture
+    }
+
+    pub fn checkerboard(&mut self) -> Rc<Texture> {
+        self.textures
+            .entry((PathBuf::from(CHECKERBOARD_KEY), Wrap::Repeat))
+            .or_insert_with(|| Rc::new(Texture::checkerboard().with_source(CHECKERBOARD_KEY)))
+            .clone()
+    }
+
+    /// Biały teksel 1x1 – tekstura dla materiałów bez własnej
+    pub fn white(&mut self) -> Rc<Texture> {
+        self.textures
+            .entry((PathBuf::from(WHITE_KEY), Wrap::Repeat))
+            .or_insert_with(|| Rc::new(Texture::from_rgba(1, 1, &[255; 4]).with_source(WHITE_KEY)))
+            .clone()
+    }
+
+    /// Mapa normalnych bez zaburzeń (0, 0, 1) – dla materiałów PBR bez własnej
+    pub fn flat_normal(&mut self) -> Rc<Texture> {
+        self.textures
+            .entry((PathBuf::from(FLAT_NORMAL_KEY), Wrap::Repeat))
+            .or_insert_with(|| {
+                Rc::new(
+                    Texture::from_rgba(1, 1, &[128, 128, 255, 255]).with_source(FLAT_NORMAL_KEY),
+                )
+            })
+            .clone()
+    }
+
+    pub fn program<V: AsRef<Path>, F: AsRef<Path>>(&mut self, vert: V, frag: F) -> Rc<Program> {
+        let key = (vert.as_ref().to_path_buf(), frag.as_ref().to_path_buf());
+        if let Some(program) = self.programs.get(&key) {
+            return program.clone();
+        }
+
+        let program = match Program::from_files(&key.0, &key.1) {
+            Ok(program) => Rc::new(program),
+            Err(e) => {
+                eprintln!("{}, using error shader", e);
+                Rc::new(Program::error_for_files(&key.0, &key.1))
+            }
+        };
+        self.programs.insert(key, program.clone());
+        program
+    }
+
+    /// Sprawdza pliki wszystkich programów i przebudowuje zmienione.
+    /// Zwraca etykietę programu i wynik dla każdego przeładowania.
+    pub fn reload_programs(&self) -> Vec<(String, Result<(), ShaderError>)> {
+        self.programs
+            .iter()
+            .filter_map(|(key, program)| {
+                program
+                    .reload_if_changed()
+                    .map(|result| (program_label(key), result))
+            })
+            .collect()
+    }
+
+    /// Usuwa zasób z cache; pamięć GPU zostanie zwolniona razem z ostatnim uchwytem
+    pub fn unload(&mut self, asset: &ResidentAsset) -> bool {
+        let path = Path::new(&asset.path);
+        match asset.kind {
+            AssetKind::Mesh => self.meshes.remove(path).is_some(),
+            AssetKind::Model => self.models.remove(path).is_some(),
+            AssetKind::Texture => {
+                let before = self.textures.len();
+                self.textures
+                    .retain(|key, _| texture_label(key) != asset.path);
+                self.textures.len() != before
+            }
+            AssetKind::Program => {
+                let before = self.programs.len();
+                self.programs
+                    .retain(|key, _| program_label(key) != asset.path);
+                self.programs.len() != before
+            }
+        }
+    }
+
+    /// Usuwa z cache wszystko, czego nie używa żaden obiekt
+    pub fn unload_unused(&mut self) {
+        self.meshes.retain(|_, mesh| Rc::strong_count(mesh) > 1);
+        self.models
+            .retain(|_, parts| parts.iter().any(|part| Rc::strong_count(&part.mesh) > 1));
+        self.textures.retain(|_, tex| Rc::strong_count(tex) > 1);
+        self.programs
+            .retain(|_, program| Rc::strong_count(program) > 1);
+    }
+
+    /// Wczytane tekstury posortowane po ścieżce (razem z wbudowanymi)
+    pub fn textures(&self) -> Vec<(String, Rc<Texture>)> {
+        let mut list: Vec<_> = self
+            .textures
+            .iter()
+            .map(|(key, texture)| (texture_label(key), texture.clone()))
+            .collect();
+        list.sort_by(|a, b| a.0.cmp(&b.0));
+        list
+    }
+
+    /// Ścieżka, pod którą siatka leży w cache (do zapisu sceny)
+    pub fn mesh_path(&self, mesh: &Rc<Mesh>) -> Option<&Path> {
+        key_of(&self.meshes, mesh, |path| path).map(PathBuf::as_path)
+    }
+
+    /// Plik OBJ i nazwa części, z której pochodzi siatka
+    pub fn model_part(&self, mesh: &Rc<Mesh>) -> Option<(&Path, &str)> {
+        let mut found: Vec<_> = self
+            .models
+            .iter()
+            .flat_map(|(path, parts)| parts.iter().map(move |part| (path.as_path(), part)))
+            .filter(|(_, part)| Rc::ptr_eq(&part.mesh, mesh))
+            .map(|(path, part)| (path, part.name.as_str()))
+            .collect();
+        found.sort();
+        found.first().copied()
+    }
+
+    /// Pliki shaderów programu: (vert, frag)
+    pub fn program_paths(&self, program: &Rc<Program>) -> Option<(&Path, &Path)> {
+        key_of(&self.programs, program, |(vert, _)| vert)
+            .map(|(vert, frag)| (vert.as_path(), frag.as_path()))
+    }
+
+    pub fn resident(&self) -> Vec<ResidentAsset> {
+        let mut list = Vec::new();
+
+        for (path, mesh) in &self.meshes {
+            list.push(ResidentAsset {
+                kind: AssetKind::Mesh,
+                path: path.display().to_string(),
+                users: Rc::strong_count(mesh) - 1,
+            });
+        }
+        for (path, parts) in &self.models {
+            list.push(ResidentAsset {
+                kind: AssetKind::Model,
+                path: path.display().to_string(),
+                users: parts
+                    .iter()
+                    .map(|part| Rc::strong_count(&part.mesh) - 1)
+                    .max()
+                    .unwrap_or(0),
+            });
+        }
+        for (key, tex) in &self.textures {
+            list.push(ResidentAsset {
+                kind: AssetKind::Texture,
+                path: texture_label(key),
+                users: Rc::strong_count(tex) - 1,
+            });
+        }
+        for (key, program) in &self.programs {
+            list.push(ResidentAsset {
+                kind: AssetKind::Program,
+                path: program_label(key),
+                users: Rc::strong_count(program) - 1,
+            });
+        }
+
+        list.sort_by(|a, b| a.path.cmp(&b.path));
+        list
+    }
+}
+
+/// Klucz zasobu w cache. Zastępczy zasób leży też pod ścieżką pliku, którego
+/// nie udało się wczytać – ścieżka ma wtedy pierwszeństwo przed kluczem wbudowanym.
+fn key_of<'a, K: Ord, T>(
+    map: &'a HashMap<K, Rc<T>>,
+    item: &Rc<T>,
+    path: impl Fn(&K) -> &PathBuf,
+) -> Option<&'a K> {
+    map.iter()
+        .filter(|(_, value)| Rc::ptr_eq(value, item))
+        .map(|(key, _)| key)
+        .min_by_key(|key| (is_builtin(path(key)), *key))
+}
+
+/// Klucz wbudowanego zasobu (`<white>`, `<quad>`, ...) zamiast ścieżki pliku
+pub fn is_builtin(path: &Path) -> bool {
+    path.to_str().is_some_and(|path| path.starts_with('<'))
+}
+
+fn texture_label((path, wrap): &(PathBuf, Wrap)) -> String {
+    match wrap {
+        Wrap::Repeat => path.display().to_string(),
+        Wrap::MirroredRepeat => format!("{} (mirrored)", path.display()),
+    }
+}
+
+fn program_label((vert, frag): &(PathBuf, PathBuf)) -> String {
+    format!("{} + {}", vert.display(), frag.display())
+}
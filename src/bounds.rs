@@ -0,0 +1,161 @@
+use cgmath::{EuclideanSpace, InnerSpace, Matrix, Matrix4, Point3, Transform, Vector3, Vector4};
+
+/// Prostopadłościan równoległy do osi
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: Point3<f32>,
+    pub max: Point3<f32>,
+}
+
+impl Aabb {
+    pub fn center(&self) -> Point3<f32> {
+        self.min.midpoint(self.max)
+    }
+
+    /// Połowa rozmiaru w każdej osi
+    pub fn extents(&self) -> Vector3<f32> {
+        (self.max - self.min) * 0.5
+    }
+
+    /// Najmniejszy AABB zawierający ten prostopadłościan po transformacji
+    pub fn transformed(&self, model: &Matrix4<f32>) -> Aabb {
+        let center = model.transform_point(self.center());
+        let e = self.extents();
+        let extents = Vector3::new(
+            model.x.x.abs() * e.x + model.y.x.abs() * e.y + model.z.x.abs() * e.z,
+            model.x.y.abs() * e.x + model.y.y.abs() * e.y + model.z.y.abs() * e.z,
+            model.x.z.abs() * e.x + model.y.z.abs() * e.y + model.z.z.abs() * e.z,
+        );
+        Aabb {
+            min: center - extents,
+            max: center + extents,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingSphere {
+    pub center: Point3<f32>,
+    pub radius: f32,
+}
+
+impl BoundingSphere {
+    /// Sfera po transformacji; przy nierównej skali promień rośnie do największej osi
+    pub fn transformed(&self, model: &Matrix4<f32>) -> BoundingSphere {
+        let scale = model
+            .x
+            .truncate()
+            .magnitude()
+            .max(model.y.truncate().magnitude())
+            .max(model.z.truncate().magnitude());
+        BoundingSphere {
+            center: model.transform_point(self.center),
+            radius: self.radius * scale,
+        }
+    }
+}
+
+/// Bryły otaczające siatki, liczone raz przy wczytaniu
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bounds {
+    pub aabb: Aabb,
+    pub sphere: BoundingSphere,
+}
+
+impl Bounds {
+    /// Z pozycji wierzchołków (pierwsze 3 floaty z każdych `stride`)
+    pub fn from_vertices(vertices: &[f32], stride: usize) -> Bounds {
+        let mut positions = vertices
+            .chunks_exact(stride)
+            .map(|v| Point3::new(v[0], v[1], v[2]));
+
+        let Some(first) = positions.next() else {
+            return Bounds::point(Point3::origin());
+        };
+        let (min, max) = positions.fold((first, first), |(min, max), p| {
+            (
+                Point3::new(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z)),
+                Point3::new(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z)),
+            )
+        });
+        let aabb = Aabb { min, max };
+
+        // sfera wokół środka AABB – wystarczająco ciasna dla testu widoczności
+        let center = aabb.center();
+        let radius = vertices
+            .chunks_exact(stride)
+            .map(|v| (Point3::new(v[0], v[1], v[2]) - center).magnitude2())
+            .fold(0.0f32, f32::max)
+            .sqrt();
+
+        Bounds {
+            aabb,
+            sphere: BoundingSphere { center, radius },
+        }
+    }
+
+    pub fn point(center: Point3<f32>) -> Bounds {
+        Bounds {
+            aabb: Aabb {
+                min: center,
+                max: center,
+            },
+            sphere: BoundingSphere {
+                center,
+                radius: 0.0,
+            },
+        }
+    }
+
+    pub fn transformed(&self, model: &Matrix4<f32>) -> Bounds {
+        Bounds {
+            aabb: self.aabb.transformed(model),
+            sphere: self.sphere.transformed(model),
+        }
+    }
+}
+
+/// Sześć płaszczyzn ostrosłupa widzenia (normalne do środka)
+pub struct Frustum {
+    planes: [Vector4<f32>; 6],
+}
+
+impl Frustum {
+    /// Płaszczyzny z macierzy `proj * view` (metoda Gribba-Hartmanna)
+    pub fn from_matrix(view_proj: &Matrix4<f32>) -> Frustum {
+        let r0 = view_proj.row(0);
+        let r1 = view_proj.row(1);
+        let r2 = view_proj.row(2);
+        let r3 = view_proj.row(3);
+
+        let planes = [r3 + r0, r3 - r0, r3 + r1, r3 - r1, r3 + r2, r3 - r2].map(|plane| {
+            let length = plane.truncate().magnitude();
+            if length > 0.0 { plane / length } else { plane }
+        });
+        Frustum { planes }
+    }
+
+    /// Najpierw tani test sfery, potem dokładniejszy AABB (bryły już w przestrzeni świata)
+    pub fn intersects(&self, bounds: &Bounds) -> bool {
+        self.intersects_sphere(&bounds.sphere) && self.intersects_aabb(&bounds.aabb)
+    }
+
+    pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool {
+        let center = sphere.center.to_vec();
+        self.planes
+            .iter()
+            .all(|plane| plane.truncate().dot(center) + plane.w >= -sphere.radius)
+    }
+
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        let center = aabb.center().to_vec();
+        let extents = aabb.extents();
+        self.planes.iter().all(|plane| {
+            let normal = plane.truncate();
+            let reach = normal.x.abs() * extents.x
+                + normal.y.abs() * extents.y
+                + normal.z.abs() * extents.z;
+            normal.dot(center) + plane.w >= -reach
+        })
+    }
+}
@@ -1,25 +1,126 @@
-use cgmath::{Matrix4, Point3, Rad, Vector3};
+use cgmath::{EuclideanSpace, InnerSpace, Matrix4, Point3, Rad, Vector3};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CameraMode {
+    /// Obrót wokół punktu `target` (radius/theta/phi)
+    Orbit,
+    /// Swobodny lot (position + yaw/pitch)
+    FreeFly,
+}
 
 pub struct Camera {
+    pub mode: CameraMode,
+
+    // orbita
     pub radius: f32,
     pub theta: f32,
     pub phi: f32,
+    pub target: Point3<f32>,
+
+    // swobodny lot
+    pub position: Point3<f32>,
+    pub yaw: f32,
+    pub pitch: f32,
+
+    /// Pionowy kąt widzenia w stopniach
+    pub fov: f32,
 }
 
+/// Maksymalne pochylenie kamery w trybie lotu (tuż przed pionem)
+pub const MAX_PITCH: f32 = std::f32::consts::FRAC_PI_2 - 0.01;
+
+/// Zakres odległości kamery od środka orbity
+pub const MIN_RADIUS: f32 = 3.0;
+pub const MAX_RADIUS: f32 = 50.0;
+
+/// Odstęp `theta` od biegunów orbity
+pub const THETA_MARGIN: f32 = 0.1;
+
 impl Camera {
     pub fn new(radius: f32, theta: f32, phi: f32) -> Self {
-        Camera { radius, theta, phi }
+        Camera {
+            mode: CameraMode::Orbit,
+            radius,
+            theta,
+            phi,
+            target: Point3::new(0.0, 0.0, 0.0),
+            position: Point3::new(0.0, 0.0, 0.0),
+            yaw: 0.0,
+            pitch: 0.0,
+            fov: 45.0,
+        }
     }
 
     pub fn view_matrix(&self) -> Matrix4<f32> {
-        let eye = spherical_to_cartesian(self.radius, self.theta, self.phi);
-        let center = Point3::new(0.0, 0.0, 0.0);
+        let eye = self.eye();
         let up = Vector3::new(0.0, 1.0, 0.0);
-        Matrix4::look_at(eye, center, up)
+        Matrix4::look_at(eye, eye + self.forward(), up)
     }
 
     pub fn proj_matrix(&self, aspect: f32) -> Matrix4<f32> {
-        cgmath::perspective(Rad(45.0f32.to_radians()), aspect, 0.1, 100.0)
+        cgmath::perspective(Rad(self.fov.to_radians()), aspect, 0.1, 100.0)
+    }
+
+    /// Pozycja kamery w świecie
+    pub fn eye(&self) -> Point3<f32> {
+        match self.mode {
+            CameraMode::Orbit => {
+                self.target + spherical_to_cartesian(self.radius, self.theta, self.phi).to_vec()
+            }
+            CameraMode::FreeFly => self.position,
+        }
+    }
+
+    /// Znormalizowany kierunek patrzenia
+    pub fn forward(&self) -> Vector3<f32> {
+        match self.mode {
+            CameraMode::Orbit => -spherical_to_cartesian(1.0, self.theta, self.phi)
+                .to_vec()
+                .normalize(),
+            CameraMode::FreeFly => Vector3::new(
+                self.pitch.cos() * self.yaw.cos(),
+                self.pitch.sin(),
+                self.pitch.cos() * self.yaw.sin(),
+            ),
+        }
+    }
+
+    /// Wektor w prawo, poziomy
+    pub fn right(&self) -> Vector3<f32> {
+        self.forward()
+            .cross(Vector3::new(0.0, 1.0, 0.0))
+            .normalize()
+    }
+
+    /// Przełącza tryb, zachowując aktualny widok
+    pub fn set_mode(&mut self, mode: CameraMode) {
+        if mode == self.mode {
+            return;
+        }
+
+        match mode {
+            CameraMode::FreeFly => {
+                self.position = self.eye();
+                self.yaw = self.phi + std::f32::consts::PI;
+                self.pitch =
+                    (self.theta - std::f32::consts::FRAC_PI_2).clamp(-MAX_PITCH, MAX_PITCH);
+            }
+            CameraMode::Orbit => {
+                // nowy środek orbity leży przed kamerą, w dotychczasowej odległości
+                self.target = self.position + self.forward() * self.radius;
+                self.theta = self.pitch + std::f32::consts::FRAC_PI_2;
+                self.phi = self.yaw - std::f32::consts::PI;
+            }
+        }
+
+        self.mode = mode;
+    }
+
+    pub fn toggle_mode(&mut self) {
+        match self.mode {
+            CameraMode::Orbit => self.set_mode(CameraMode::FreeFly),
+            CameraMode::FreeFly => self.set_mode(CameraMode::Orbit),
+        }
     }
 }
 
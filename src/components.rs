@@ -0,0 +1,183 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use cgmath::{InnerSpace, Matrix4, Rad, Vector3};
+
+use crate::assets::AssetManager;
+use crate::material::{
+    BASIC_FRAG, BASIC_VERT, DIFFUSE_SLOT, Material, MaterialParam, PBR_FRAG, PBR_VERT, RenderFlags,
+    RenderMode,
+};
+use crate::mesh::{Mesh, ObjMaterial, ObjPart, ObjPbr};
+
+/// Nazwa do logów i GUI (nie musi być unikalna)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Name(pub String);
+
+/// Siatka rysowana z materiałem; encja potrzebuje też `WorldTransform`
+#[derive(Clone)]
+pub struct Renderable {
+    pub mesh: Rc<Mesh>,
+    pub material: Rc<Material>,
+    /// Mnożnik koloru tego egzemplarza (atrybut instancji, nie uniform)
+    pub tint: Vector3<f32>,
+}
+
+impl Renderable {
+    pub fn new(mesh: Rc<Mesh>, material: Rc<Material>) -> Self {
+        Renderable {
+            mesh,
+            material,
+            tint: Vector3::new(1.0, 1.0, 1.0),
+        }
+    }
+
+    pub fn with_tint(mut self, tint: Vector3<f32>) -> Self {
+        self.tint = tint;
+        self
+    }
+
+    /// Osobny obiekt dla każdej części OBJ, z kolorem i teksturą z jej materiału
+    pub fn from_obj_parts(
+        parts: Vec<ObjPart>,
+        assets: &mut AssetManager,
+    ) -> Vec<(Name, Renderable)> {
+        let program = assets.program(BASIC_VERT, BASIC_FRAG);
+        // wiele części dzieli materiał z MTL – ostrzegamy raz na plik
+        let mut unused_normal_maps: HashSet<PathBuf> = HashSet::new();
+
+        parts
+            .into_iter()
+            .map(|part| {
+                let name = Name(part.name.clone());
+                let Some(obj_material) = part.material else {
+                    let white = Vector3::new(1.0, 1.0, 1.0);
+                    let material =
+                        Material::basic(&part.name, program.clone(), white, white, assets.white());
+                    return (name, Renderable::new(part.mesh, Rc::new(material)));
+                };
+                // materiały z parametrami PBR (Pr/Pm) idą do pbr.frag
+                if let Some(pbr) = obj_material.pbr.clone() {
+                    let material = obj_pbr_material(obj_material, pbr, assets);
+                    return (name, Renderable::new(part.mesh, Rc::new(material)));
+                }
+
+                if let Some(path) = &obj_material.normal_texture
+                    && unused_normal_maps.insert(path.clone())
+                {
+                    eprintln!(
+                        "Warning: normal map {:?} is not used by the basic shader",
+                        path
+                    );
+                }
+
+                let color = Vector3::from(obj_material.diffuse);
+                let texture = match &obj_material.diffuse_texture {
+                    Some(path) => assets.texture(path),
+                    None => assets.white(),
+                };
+                let material = with_obj_opacity(
+                    Material::basic(&obj_material.name, program.clone(), color, color, texture),
+                    &obj_material,
+                );
+
+                (name, Renderable::new(part.mesh, Rc::new(material)))
+            })
+            .collect()
+    }
+}
+
+/// Stały obrót w przestrzeni lokalnej – dzieci w grafie sceny obracają się razem z encją
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RotationAnimation {
+    pub axis: Vector3<f32>,
+    pub speed: f32,
+}
+
+impl RotationAnimation {
+    pub fn matrix(&self, time: f32) -> Matrix4<f32> {
+        Matrix4::from_axis_angle(self.axis.normalize(), Rad(time * self.speed))
+    }
+}
+
+/// Przelewanie kolorów gradientu materiału tam i z powrotem
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColorAnimation {
+    pub speed: f32,
+}
+
+impl ColorAnimation {
+    /// Ustawia animowane kolory gradientu po `Material::apply` – encje
+    /// z animacją koloru są rysowane poza wspólnymi partiami
+    pub fn apply(&self, material: &Material, time: f32) {
+        let (Some(c1), Some(c2)) = (material.vec3("u_color1"), material.vec3("u_color2")) else {
+            return;
+        };
+
+        let t = (time * self.speed).sin() * 0.5 + 0.5;
+        material
+            .program
+            .set_vec3("u_color1", &(c1 * (1.0 - t) + c2 * t));
+        material
+            .program
+            .set_vec3("u_color2", &(c2 * (1.0 - t) + c1 * t));
+    }
+}
+
+fn obj_pbr_material(obj_material: ObjMaterial, pbr: ObjPbr, assets: &mut AssetManager) -> Material {
+    let program = assets.program(PBR_VERT, PBR_FRAG);
+    let white = assets.white();
+    let flat_normal = assets.flat_normal();
+
+    // tekstura emisji bez Ke byłaby wygaszona do zera
+    let emissive = if pbr.emissive_texture.is_some() && pbr.emissive == [0.0; 3] {
+        [1.0; 3]
+    } else {
+        pbr.emissive
+    };
+
+    let mut material = Material::pbr(&obj_material.name, program, white, flat_normal)
+        .with_param(
+            "u_albedo",
+            MaterialParam::Vec3(Vector3::from(obj_material.diffuse)),
+        )
+        .with_param("u_metallic", MaterialParam::Float(pbr.metallic))
+        .with_param("u_roughness", MaterialParam::Float(pbr.roughness))
+        .with_param("u_emissive", MaterialParam::Vec3(Vector3::from(emissive)));
+    material = with_obj_opacity(material, &obj_material);
+
+    let maps = [
+        (DIFFUSE_SLOT, obj_material.diffuse_texture),
+        ("u_normal_map", obj_material.normal_texture),
+        ("u_metallic_map", pbr.metallic_texture),
+        ("u_roughness_map", pbr.roughness_texture),
+        ("u_emissive_map", pbr.emissive_texture),
+    ];
+    for (slot, path) in maps {
+        if let Some(path) = path {
+            material = material.with_texture(slot, assets.texture(path));
+        }
+    }
+    material
+}
+
+/// Przezroczystość z MTL (`d` < 1): z teksturą to zwykle liście/kwiaty,
+/// więc alpha cutout; bez tekstury – mieszanie z `u_opacity` = d
+fn with_obj_opacity(material: Material, obj_material: &ObjMaterial) -> Material {
+    if obj_material.alpha >= 1.0 {
+        return material;
+    }
+
+    let mode = if obj_material.diffuse_texture.is_some() {
+        RenderMode::Cutout
+    } else {
+        RenderMode::Blended
+    };
+    material
+        .with_param("u_opacity", MaterialParam::Float(obj_material.alpha))
+        .with_flags(RenderFlags {
+            mode,
+            ..RenderFlags::default()
+        })
+}
@@ -0,0 +1,350 @@
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+
+/// Identyfikator encji – indeks w tablicach komponentów
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Entity(u32);
+
+impl Entity {
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// Komponenty jednego typu indeksowane numerem encji
+pub struct Storage<T> {
+    items: Vec<Option<T>>,
+    /// Ustawiane przy wstawieniu i `get_mut`, czyszczone przez `World::clear_changed`
+    changed: Vec<bool>,
+    /// Posortowane indeksy encji, które mają ten komponent – po nich idą zapytania
+    indices: Vec<u32>,
+}
+
+impl<T> Storage<T> {
+    fn new() -> Self {
+        Storage {
+            items: Vec::new(),
+            changed: Vec::new(),
+            indices: Vec::new(),
+        }
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.items.get(index)?.as_ref()
+    }
+
+    fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        let item = self.items.get_mut(index)?.as_mut()?;
+        self.changed[index] = true;
+        Some(item)
+    }
+
+    /// Zwraca `true`, gdy encja nie miała jeszcze tego komponentu
+    fn insert(&mut self, index: usize, value: T) -> bool {
+        if index >= self.items.len() {
+            self.items.resize_with(index + 1, || None);
+            self.changed.resize(index + 1, false);
+        }
+        let added = self.items[index].replace(value).is_none();
+        self.changed[index] = true;
+        if added {
+            // encje powstają po kolei, więc zwykle to dopisanie na końcu
+            let position = self.indices.partition_point(|&i| (i as usize) < index);
+            self.indices.insert(position, index as u32);
+        }
+        added
+    }
+
+    fn remove(&mut self, index: usize) -> Option<T> {
+        let item = self.items.get_mut(index)?.take()?;
+        self.changed[index] = false;
+        if let Ok(position) = self.indices.binary_search(&(index as u32)) {
+            self.indices.remove(position);
+        }
+        Some(item)
+    }
+}
+
+/// Magazyn komponentów bez znajomości ich typu
+trait AnyStorage {
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    fn clear_changed(&mut self);
+    /// Usuwa komponent encji, jeśli go miała
+    fn remove_entity(&mut self, index: usize) -> bool;
+}
+
+impl<T: 'static> AnyStorage for Storage<T> {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn clear_changed(&mut self) {
+        self.changed.fill(false);
+    }
+
+    fn remove_entity(&mut self, index: usize) -> bool {
+        self.remove(index).is_some()
+    }
+}
+
+/// Encje i ich komponenty. Komponentem może być dowolny typ `'static` –
+/// kod gry dodaje własne dane bez zmieniania struktur silnika.
+/// Indeksy usuniętych encji nie są używane ponownie.
+#[derive(Default)]
+pub struct World {
+    /// Czy encja o danym indeksie istnieje (`false` po `despawn`)
+    alive: Vec<bool>,
+    storages: HashMap<TypeId, Box<dyn AnyStorage>>,
+    /// Licznik zmian struktury – patrz `structure_version`
+    version: u64,
+}
+
+impl World {
+    pub fn new() -> Self {
+        World::default()
+    }
+
+    /// Nowa encja; komponenty dokłada się przez `EntityBuilder::with`
+    pub fn spawn(&mut self) -> EntityBuilder<'_> {
+        let entity = Entity(self.alive.len() as u32);
+        self.alive.push(true);
+        self.version += 1;
+        EntityBuilder {
+            world: self,
+            entity,
+        }
+    }
+
+    /// Usuwa encję razem ze wszystkimi jej komponentami
+    pub fn despawn(&mut self, entity: Entity) -> bool {
+        if !self.contains(entity) {
+            return false;
+        }
+        for storage in self.storages.values_mut() {
+            storage.remove_entity(entity.index());
+        }
+        self.alive[entity.index()] = false;
+        self.version += 1;
+        true
+    }
+
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.alive.get(entity.index()).copied().unwrap_or(false)
+    }
+
+    /// Górna granica indeksów encji (utworzone, razem z usuniętymi) –
+    /// rośnie tylko przy `spawn`
+    pub fn len(&self) -> usize {
+        self.alive.len()
+    }
+
+    /// Rośnie przy `spawn`, `despawn` oraz dodaniu lub usunięciu komponentu;
+    /// zmiana wartości istniejącego komponentu go nie zmienia
+    pub fn structure_version(&self) -> u64 {
+        self.version
+    }
+
+    pub fn insert<T: 'static>(&mut self, entity: Entity, component: T) {
+        if !self.contains(entity) {
+            eprintln!(
+                "Component {} for missing entity {:?}",
+                std::any::type_name::<T>(),
+                entity
+            );
+            return;
+        }
+        if self.storage_mut::<T>().insert(entity.index(), component) {
+            self.version += 1;
+        }
+    }
+
+    /// Zdejmuje komponent z encji i go zwraca
+    pub fn remove<T: 'static>(&mut self, entity: Entity) -> Option<T> {
+        let component = self
+            .storages
+            .get_mut(&TypeId::of::<T>())?
+            .as_any_mut()
+            .downcast_mut::<Storage<T>>()?
+            .remove(entity.index())?;
+        self.version += 1;
+        Some(component)
+    }
+
+    pub fn get<T: 'static>(&self, entity: Entity) -> Option<&T> {
+        self.storage::<T>()?.get(entity.index())
+    }
+
+    /// Zmiana komponentu – oznacza go jako zmieniony (patrz `is_changed`)
+    pub fn get_mut<T: 'static>(&mut self, entity: Entity) -> Option<&mut T> {
+        self.storages
+            .get_mut(&TypeId::of::<T>())?
+            .as_any_mut()
+            .downcast_mut::<Storage<T>>()?
+            .get_mut(entity.index())
+    }
+
+    pub fn has<T: 'static>(&self, entity: Entity) -> bool {
+        self.get::<T>(entity).is_some()
+    }
+
+    /// Czy komponent był wstawiony lub zmieniany od ostatniego `clear_changed`
+    pub fn is_changed<T: 'static>(&self, entity: Entity) -> bool {
+        self.storage::<T>()
+            .and_then(|storage| storage.changed.get(entity.index()).copied())
+            .unwrap_or(false)
+    }
+
+    pub fn any_changed<T: 'static>(&self) -> bool {
+        self.storage::<T>()
+            .is_some_and(|storage| storage.changed.contains(&true))
+    }
+
+    pub fn clear_changed<T: 'static>(&mut self) {
+        if let Some(storage) = self.storages.get_mut(&TypeId::of::<T>()) {
+            storage.clear_changed();
+        }
+    }
+
+    /// Encje mające wszystkie komponenty z `Q`, np. `world.query::<(&Transform, &Name)>()`.
+    /// `Option<&T>` w krotce dopuszcza encje bez tego komponentu.
+    /// Przechodzi tylko po encjach z najmniej licznego wymaganego komponentu.
+    pub fn query<Q: Query>(&self) -> impl Iterator<Item = (Entity, Q::Item<'_>)> {
+        let fetch = Q::fetch(self);
+        let (indices, all) = match fetch.as_ref().map(|fetch| Q::indices(fetch)) {
+            Some(Some(indices)) => (Some(indices.iter().map(|&i| i as usize)), None),
+            // same `Option<&T>` – wszystkie encje
+            Some(None) => (None, Some(0..self.alive.len())),
+            // brak magazynu wymaganego komponentu
+            None => (None, None),
+        };
+        let candidates = indices
+            .into_iter()
+            .flatten()
+            .chain(all.into_iter().flatten());
+
+        candidates.filter_map(move |index| {
+            if !self.alive[index] {
+                return None;
+            }
+            let item = Q::get(fetch.as_ref()?, index)?;
+            Some((Entity(index as u32), item))
+        })
+    }
+
+    pub fn storage<T: 'static>(&self) -> Option<&Storage<T>> {
+        self.storages
+            .get(&TypeId::of::<T>())?
+            .as_any()
+            .downcast_ref::<Storage<T>>()
+    }
+
+    fn storage_mut<T: 'static>(&mut self) -> &mut Storage<T> {
+        self.storages
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(Storage::<T>::new()))
+            .as_any_mut()
+            .downcast_mut::<Storage<T>>()
+            .expect("storage registered under a different type")
+    }
+}
+
+/// Encja w trakcie składania: `world.spawn().with(a).with(b).id()`
+pub struct EntityBuilder<'w> {
+    world: &'w mut World,
+    entity: Entity,
+}
+
+impl EntityBuilder<'_> {
+    pub fn with<T: 'static>(self, component: T) -> Self {
+        self.world.insert(self.entity, component);
+        self
+    }
+
+    pub fn id(&self) -> Entity {
+        self.entity
+    }
+}
+
+/// Zestaw komponentów pobieranych razem przez `World::query`
+pub trait Query {
+    type Fetch<'w>;
+    type Item<'w>;
+
+    /// `None`, gdy żadna encja nie ma wymaganego komponentu
+    fn fetch(world: &World) -> Option<Self::Fetch<'_>>;
+    /// Indeksy encji, wśród których są wszystkie pasujące; `None` – bez ograniczeń
+    fn indices<'w>(fetch: &Self::Fetch<'w>) -> Option<&'w [u32]>;
+    fn get<'w>(fetch: &Self::Fetch<'w>, index: usize) -> Option<Self::Item<'w>>;
+}
+
+impl<T: 'static> Query for &T {
+    type Fetch<'w> = &'w Storage<T>;
+    type Item<'w> = &'w T;
+
+    fn fetch(world: &World) -> Option<Self::Fetch<'_>> {
+        world.storage::<T>()
+    }
+
+    fn indices<'w>(fetch: &Self::Fetch<'w>) -> Option<&'w [u32]> {
+        Some(&fetch.indices)
+    }
+
+    fn get<'w>(fetch: &Self::Fetch<'w>, index: usize) -> Option<Self::Item<'w>> {
+        fetch.get(index)
+    }
+}
+
+impl<T: 'static> Query for Option<&T> {
+    type Fetch<'w> = Option<&'w Storage<T>>;
+    type Item<'w> = Option<&'w T>;
+
+    fn fetch(world: &World) -> Option<Self::Fetch<'_>> {
+        Some(world.storage::<T>())
+    }
+
+    fn indices<'w>(_fetch: &Self::Fetch<'w>) -> Option<&'w [u32]> {
+        None
+    }
+
+    fn get<'w>(fetch: &Self::Fetch<'w>, index: usize) -> Option<Self::Item<'w>> {
+        Some(fetch.and_then(|storage| storage.get(index)))
+    }
+}
+
+macro_rules! tuple_query {
+    ($($name:ident),+) => {
+        impl<$($name: Query),+> Query for ($($name,)+) {
+            type Fetch<'w> = ($($name::Fetch<'w>,)+);
+            type Item<'w> = ($($name::Item<'w>,)+);
+
+            fn fetch(world: &World) -> Option<Self::Fetch<'_>> {
+                Some(($($name::fetch(world)?,)+))
+            }
+
+            #[allow(non_snake_case)]
+            fn indices<'w>(fetch: &Self::Fetch<'w>) -> Option<&'w [u32]> {
+                let ($($name,)+) = fetch;
+                [$($name::indices($name)),+]
+                    .into_iter()
+                    .flatten()
+                    .min_by_key(|indices| indices.len())
+            }
+
+            #[allow(non_snake_case)]
+            fn get<'w>(fetch: &Self::Fetch<'w>, index: usize) -> Option<Self::Item<'w>> {
+                let ($($name,)+) = fetch;
+                Some(($($name::get($name, index)?,)+))
+            }
+        }
+    };
+}
+
+tuple_query!(A);
+tuple_query!(A, B);
+tuple_query!(A, B, C);
+tuple_query!(A, B, C, D);
@@ -1,45 +1,165 @@
-use cgmath::{Matrix4, Rad, Vector3};
-
+use cgmath::{EuclideanSpace, Matrix4, Point3, Vector2, Vector3};
 use egui_glow::glow;
-use glfw::{Action, Context, Key};
-use rand::Rng;
+use glfw::{Action, Context, Key, MouseButton};
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::sync::Arc;
 use std::sync::mpsc::Receiver;
+use std::time::{SystemTime, UNIX_EPOCH};
 
-use crate::camera::Camera;
+use crate::animation::{self, AnimationClip, Animator, PlaybackMode};
+use crate::assets::{AssetManager, ResidentAsset};
+use crate::bounds::Frustum;
+use crate::camera::{Camera, CameraMode, MAX_RADIUS, MIN_RADIUS, THETA_MARGIN};
+use crate::components::Renderable;
+use crate::ecs::{Entity, World};
+use crate::file_menu::{FileAction, FileMenu};
+use crate::framebuffer::{Framebuffer, FramebufferError};
+use crate::gizmo::{Gizmo, Viewport};
 use crate::glcontext;
 use crate::gui::Gui;
+use crate::input::{self, GuiFocus, InputState};
+use crate::inspector::{self, Inspector};
+use crate::instancing::{self, Batch, DrawItem, InstanceBuffer, InstanceData};
+use crate::light::{LIGHTS_BINDING, Light, LightBuffer, LightKind, MAX_LIGHTS};
+use crate::material::{BASIC_FRAG, BASIC_VERT, DIFFUSE_SLOT, Material, RenderMode};
 use crate::mesh::Mesh;
-use crate::scene_object::SceneObject;
-use crate::shader::Program;
+use crate::picking::{self, Hit};
+use crate::render_queue::RenderQueue;
+use crate::scene;
+use crate::scene_graph::{SceneGraph, WorldTransform};
+use crate::shader::{Program, ShaderVariable};
+use crate::shadow::{self, ShadowMap, ShadowSettings};
 use crate::textures::Texture;
 
+/// Dane pokazywane w oknach debugowych, zbierane raz na klatkę
+struct DebugInfo {
+    time: f32,
+    objects_count: usize,
+    /// Wywołania rysowania: nieprzezroczyste, cutout, przezroczyste
+    draw_calls: [usize; 3],
+    /// Obiekty narysowane / odrzucone przez frustum culling
+    drawn: usize,
+    culled: usize,
+    lights_count: usize,
+    /// Nazwa materiału i liczba obiektów, które go używają
+    materials: Vec<(String, usize)>,
+    triangles_count: i32,
+    /// Sumy po siatkach sceny (każda raz): wierzchołki w VBO, przed
+    /// złączeniem duplikatów i indeksy
+    mesh_counts: (i32, i32, i32),
+    resident: Vec<ResidentAsset>,
+    /// Wczytane tekstury do wyboru w inspektorze
+    textures: Vec<(String, Rc<Texture>)>,
+    /// Ostatnie trafienie kursorem
+    hit: Option<Hit>,
+    shader_errors: Vec<(String, String)>,
+    uniforms: Vec<ShaderVariable>,
+    attributes: Vec<ShaderVariable>,
+}
+
+const SCREENSHOT_DIR: &str = "screenshots";
+
+/// Jednostka tekstury mapy cieni – materiały zajmują jednostki od 0
+const SHADOW_MAP_UNIT: u32 = 8;
+
+/// Rozdzielczości mapy cieni do wyboru w GUI
+const SHADOW_RESOLUTIONS: &[i32] = &[512, 1024, 2048, 4096];
+
+/// Co ile sekund sprawdzamy, czy pliki shaderów się zmieniły
+const SHADER_CHECK_INTERVAL: f32 = 0.5;
+
+enum AssetAction {
+    Unload(ResidentAsset),
+    UnloadUnused,
+    /// Wczytanie tekstury koloru dla encji (z inspektora)
+    LoadTexture(Entity, String),
+}
+
+// Kolejność pól ma znaczenie: zasoby GPU muszą zostać zwolnione przed
+// zamknięciem okna (i kontekstu GL), a pola są dropowane od góry.
 pub struct Engine {
-    glfw: glfw::Glfw,
-    window: glfw::Window,
-    events: Receiver<(f64, glfw::WindowEvent)>,
-    program: Program,
-    objects: Vec<SceneObject>,
+    world: World,
+    scene_graph: SceneGraph,
+    lights: Vec<Light>,
+    ambient: Vector3<f32>,
+    light_buffer: LightBuffer,
+    instances: InstanceBuffer,
+    /// Liczba wywołań rysowania w ostatniej klatce w każdym przebiegu (bez cieni)
+    draw_calls: Cell<[usize; 3]>,
+    /// (narysowane, odrzucone) obiekty w ostatniej klatce
+    visibility: Cell<(usize, usize)>,
+    /// Ostatnie trafienie kursorem; zaznaczona encja jest w `inspector`
+    hit: Option<Hit>,
+    inspector: Inspector,
+    gizmo: Gizmo,
+    file_menu: FileMenu,
+    shadow_map: Option<ShadowMap>,
+    shadow_settings: ShadowSettings,
+    program: Rc<Program>,
+    shadow_program: Rc<Program>,
+    assets: AssetManager,
     camera: Camera,
+    input: InputState,
     last_time: f32,
+    last_shader_check: f32,
+    /// Logi nieudanych kompilacji/linkowania według programu; wpis znika,
+    /// gdy ten sam program przebuduje się poprawnie
+    shader_errors: BTreeMap<String, String>,
 
     gui: Gui,
+
+    events: Receiver<(f64, glfw::WindowEvent)>,
+    window: glfw::Window,
+    glfw: glfw::Glfw,
 }
 
 impl Engine {
-    pub fn new(width: u32, height: u32, title: &str) -> Self {
+    pub fn new<P: AsRef<Path>>(width: u32, height: u32, title: &str, scene_path: P) -> Self {
+        Engine::create(width, height, title, scene_path, true)
+    }
+
+    /// Silnik bez widocznego okna – do renderowania do plików (`save_screenshot`)
+    pub fn new_offscreen<P: AsRef<Path>>(width: u32, height: u32, scene_path: P) -> Self {
+        Engine::create(
+            width,
+            height,
+            "Rust OBJ Scene (offscreen)",
+            scene_path,
+            false,
+        )
+    }
+
+    fn create<P: AsRef<Path>>(
+        width: u32,
+        height: u32,
+        title: &str,
+        scene_path: P,
+        visible: bool,
+    ) -> Self {
         let mut glfw = glcontext::init_glfw();
 
-        let (mut window, events) = glcontext::create_window(&mut glfw, width, height, title);
+        let (mut window, events) = if visible {
+            glcontext::create_window(&mut glfw, width, height, title)
+        } else {
+            glcontext::create_hidden_window(&mut glfw, width, height, title)
+        };
         window.make_current();
 
         glcontext::init_gl(&mut window);
 
+        let mut assets = AssetManager::new();
+
         // let program = Program::new(shaders::basic::VERT, shaders::basic::FRAG);
-        let program = Program::from_files("assets/shaders/basic.vert", "assets/shaders/basic.frag");
-        program.use_program();
-        program.set_int("u_diffuse", 0);
+        let program = assets.program(BASIC_VERT, BASIC_FRAG);
+        let shadow_program =
+            assets.program("assets/shaders/shadow.vert", "assets/shaders/shadow.frag");
+
+        let shadow_settings = ShadowSettings::default();
+        let shadow_map = Engine::create_shadow_map(shadow_settings.resolution);
 
         let glow_ctx = unsafe {
             glow::Context::from_loader_function(|s| window.get_proc_address(s) as *const _)
@@ -48,181 +168,99 @@ impl Engine {
 
         let gui = Gui::new(glow_ctx.clone());
 
-        // Ładowanie siatek
-        let ground_mesh = Rc::new(Mesh::from_obj("assets/models/ground-large.obj"));
-        let tree_mesh = Rc::new(Mesh::from_obj("assets/models/palm.obj"));
-        let house_mesh = Rc::new(Mesh::from_obj("assets/models/kaktus.obj"));
-        let rock_mesh = Rc::new(Mesh::from_obj("assets/models/rock.obj"));
-        let flower_mesh = Rc::new(Mesh::quad());
-
-        let mut objects = Vec::new();
-        // let flower_positions = [
-        //     Vector3::new(-2.0, 0.0, 1.0),
-        //     Vector3::new(-1.0, 0.0, 3.0),
-        //     Vector3::new(0.0, 0.0, 2.5),
-        //     Vector3::new(1.0, 0.0, 1.5),
-        //     Vector3::new(2.0, 0.0, 3.0),
-        // ];
-
-        // --- Meshe ---
-        let flower_tex = Rc::new(Texture::from_file("assets/textures/flower32bit.png"));
-        let ground_tex = Rc::new(Texture::from_file("assets/textures/ground.jpg"));
-        ground_tex.set_mirrored_repeat();
-        let cactus_tex = Rc::new(Texture::from_file("assets/textures/cactus.jpg"));
-        let rock_tex = Rc::new(Texture::from_file("assets/textures/rock.jpg"));
-
-        let mut rng = rand::thread_rng();
-        let flower_count = 120;
-
-        for _ in 0..flower_count {
-            // ZAKRES X,Z dopasuj do rozmiaru swojego ground-large.obj
-            // Załóżmy np. ziemia ~ od -8 do 8 w obu osiach:
-            let x = rng.gen_range(-8.0, 8.0);
-            let z = rng.gen_range(-8.0, 8.0);
-
-            // losowa skala kwiatka
-            let scale = rng.gen_range(0.4, 1.0);
-
-            // losowy obrót wokół Y
-            let rotation = rng.gen_range(0.0, std::f32::consts::TAU);
-
-            // pierwszy quad
-            let model1 = Matrix4::from_translation(Vector3::new(x, 0.0, z))
-                * Matrix4::from_angle_y(Rad(rotation))
-                * Matrix4::from_scale(scale);
-
-            objects.push(
-                SceneObject::new(
-                    flower_mesh.clone(),
-                    model1,
-                    Vector3::new(1.0, 1.0, 1.0),
-                    Vector3::new(1.0, 1.0, 1.0),
-                )
-                .with_texture(flower_tex.clone(), true),
-            );
-
-            // drugi quad – obrócony o 90° względem pierwszego
-            let model2 = Matrix4::from_translation(Vector3::new(x, 0.0, z))
-                * Matrix4::from_angle_y(Rad(rotation + std::f32::consts::FRAC_PI_2))
-                * Matrix4::from_scale(scale);
-
-            objects.push(
-                SceneObject::new(
-                    flower_mesh.clone(),
-                    model2,
-                    Vector3::new(1.0, 1.0, 1.0),
-                    Vector3::new(1.0, 1.0, 1.0),
-                )
-                .with_texture(flower_tex.clone(), true),
-            );
-        }
-        //    for pos in &flower_positions {
-        //        // pierwszy quad
-        //        let model1 = Matrix4::from_translation(*pos) * Matrix4::from_scale(0.7);
-        //        objects.push(
-        //            SceneObject::new(
-        //                flower_mesh.clone(),
-        //                model1,
-        //                Vector3::new(1.0, 1.0, 1.0),
-        //                Vector3::new(1.0, 1.0, 1.0),
-        //            )
-        //            .with_texture(flower_tex.clone(), true),
-        //        );
-
-        //        // drugi quad – obrócony o 90 stopni wokół Y
-        //        let model2 = Matrix4::from_translation(*pos)
-        //            * Matrix4::from_angle_y(Rad(std::f32::consts::FRAC_PI_2))
-        //            * Matrix4::from_scale(0.7);
-        //        objects.push(
-        //            SceneObject::new(
-        //                flower_mesh.clone(),
-        //                model2,
-        //                Vector3::new(1.0, 1.0, 1.0),
-        //                Vector3::new(1.0, 1.0, 1.0),
-        //            )
-        //            .with_texture(flower_tex.clone(), true),
-        //        );
-        //    }
-        // Podłoże
-        let ground_model = Matrix4::from_scale(1.0);
-        objects.push(
-            SceneObject::new(
-                ground_mesh.clone(),
-                ground_model,
-                Vector3::new(0.6, 0.6, 0.6),
-                Vector3::new(0.8, 0.8, 0.8),
-            )
-            .with_ground(true)
-            .with_texture(ground_tex.clone(), false),
-        );
-
-        // Drzewo – animacja koloru
-        let tree_model = Matrix4::from_translation(cgmath::Vector3::new(-3.0, 0.0, -2.0));
-        objects.push(
-            SceneObject::new(
-                tree_mesh.clone(),
-                tree_model,
-                Vector3::new(0.1, 0.5, 0.1),
-                Vector3::new(0.6, 0.8, 0.3),
-            )
-            .with_color_animation(1.0),
-        );
-
-        // Dom – statyczny
-        let house_model = Matrix4::from_translation(cgmath::Vector3::new(2.0, 0.0, -4.0));
-        objects.push(
-            SceneObject::new(
-                house_mesh.clone(),
-                house_model,
-                Vector3::new(1.0, 1.0, 1.0),
-                Vector3::new(1.0, 1.0, 1.0),
-            )
-            .with_texture(cactus_tex.clone(), false),
-        );
-
-        // Skała 1 – obrót
-        let rock_model1 = Matrix4::from_translation(cgmath::Vector3::new(-1.0, 0.0, 2.0))
-            * Matrix4::from_scale(0.8);
-        objects.push(
-            SceneObject::new(
-                rock_mesh.clone(),
-                rock_model1,
-                Vector3::new(1.0, 1.0, 1.0),
-                Vector3::new(1.0, 1.0, 1.0),
-            )
-            .with_rotation(cgmath::Vector3::new(0.0, 1.0, 0.0), 1.0)
-            .with_texture(rock_tex.clone(), false),
-        );
-
-        // Skała 2 – obrót + kolor
-        let rock_model2 = Matrix4::from_translation(cgmath::Vector3::new(3.0, 0.0, 1.0))
-            * Matrix4::from_scale(0.5);
-        objects.push(
-            SceneObject::new(
-                rock_mesh.clone(),
-                rock_model2,
-                Vector3::new(1.0, 1.0, 1.0),
-                Vector3::new(1.0, 1.0, 1.0),
-            )
-            .with_rotation(cgmath::Vector3::new(0.0, 1.0, 0.0), 2.0)
-            .with_color_animation(2.0)
-            .with_texture(rock_tex.clone(), false),
-        );
+        // Ładowanie sceny z pliku
+        let scene = scene::load_scene(&scene_path, &mut assets).unwrap_or_else(|e| {
+            eprintln!("{}, starting with an empty scene", e);
+            scene::Scene {
+                world: World::new(),
+                lights: vec![Light::sun()],
+                ambient: Vector3::new(0.3, 0.3, 0.3),
+            }
+        });
 
         let camera = Camera::new(12.0, 0.5, 0.8);
 
         let last_time = glfw.get_time() as f32;
 
-        Engine {
-            glfw,
-            window,
-            events,
+        let mut engine = Engine {
+            world: scene.world,
+            scene_graph: SceneGraph::new(),
+            lights: Vec::new(),
+            ambient: scene.ambient,
+            light_buffer: LightBuffer::new(),
+            instances: InstanceBuffer::new(),
+            draw_calls: Cell::new([0; 3]),
+            visibility: Cell::new((0, 0)),
+            hit: None,
+            inspector: Inspector::new(),
+            gizmo: Gizmo::new(),
+            file_menu: FileMenu::new(&scene_path),
+            shadow_map,
+            shadow_settings,
             program,
-            objects,
+            shadow_program,
+            assets,
             camera,
+            input: InputState::default(),
             last_time,
+            last_shader_check: last_time,
+            shader_errors: BTreeMap::new(),
             gui,
+            events,
+            window,
+            glfw,
+        };
+        for light in scene.lights {
+            engine.add_light(light);
+        }
+        engine.scene_graph.update(&mut engine.world, last_time);
+        engine
+    }
+
+    /// Zastępuje bieżącą scenę wczytaną z pliku; przy błędzie zostaje stara
+    fn open_scene(&mut self, path: PathBuf) {
+        let scene = match scene::load_scene(&path, &mut self.assets) {
+            Ok(scene) => scene,
+            Err(e) => {
+                self.file_menu.opened(path, Err(e));
+                return;
+            }
+        };
+
+        self.world = scene.world;
+        self.lights.clear();
+        for light in scene.lights {
+            self.add_light(light);
         }
+        self.ambient = scene.ambient;
+        // encje starego świata nic już nie znaczą
+        self.scene_graph = SceneGraph::new();
+        self.inspector.selected = None;
+        self.hit = None;
+        self.gizmo.end_drag();
+        self.scene_graph.update(&mut self.world, self.last_time);
+        // zasoby używane tylko przez starą scenę
+        self.assets.unload_unused();
+
+        self.file_menu.opened(path, Ok(()));
+    }
+
+    /// Dodaje światło do sceny; zwraca jego indeks
+    pub fn add_light(&mut self, light: Light) -> usize {
+        if self.lights.len() == MAX_LIGHTS {
+            eprintln!(
+                "More than {} lights in the scene, the rest will be ignored by the shader",
+                MAX_LIGHTS
+            );
+        }
+        self.lights.push(light);
+        self.lights.len() - 1
+    }
+
+    fn create_shadow_map(resolution: i32) -> Option<ShadowMap> {
+        ShadowMap::new(resolution)
+            .map_err(|e| eprintln!("Shadow map {0}x{0}: {1}, shadows disabled", resolution, e))
+            .ok()
     }
 
     pub fn run(&mut self) {
@@ -233,49 +271,408 @@ impl Engine {
 
             self.glfw.poll_events();
 
+            if current_time - self.last_shader_check > SHADER_CHECK_INTERVAL {
+                self.last_shader_check = current_time;
+                self.reload_shaders();
+            }
+
             // 1. Nowa klatka egui
             self.gui.begin_frame();
 
-            // 2. Obsługa eventów z glfw
+            // 2. Obsługa eventów z glfw; wejście zajęte przez egui
+            // w poprzedniej klatce nie trafia do kamery ani skrótów
+            let focus = self.gui_focus();
+            let mut click = false;
+            let mut release = false;
             for (_, event) in glfw::flush_messages(&self.events) {
                 // przekazujemy do egui
                 self.gui.on_glfw_event(&self.window, &event);
 
                 // równolegle logika silnika (ESC itd.)
                 match event {
-                    glfw::WindowEvent::Key(Key::Escape, _, Action::Press, _) => {
-                        self.window.set_should_close(true);
+                    // skróty tylko bez modyfikatorów – Ctrl+C/Ctrl+V należą do schowka
+                    glfw::WindowEvent::Key(key, _, Action::Press, mods)
+                        if mods.is_empty() && !focus.keyboard =>
+                    {
+                        match key {
+                            Key::Escape => self.window.set_should_close(true),
+                            Key::F12 => self.take_screenshot(),
+                            // C – przełączenie orbita / swobodny lot
+                            Key::C => input::toggle_camera_mode(&mut self.window, &mut self.camera),
+                            _ => {}
+                        }
+                    }
+                    glfw::WindowEvent::FramebufferSize(width, height) => {
+                        glcontext::set_viewport(width, height);
+                    }
+                    // lewy przycisk – uchwyt gizmo albo wybór obiektu pod kursorem
+                    // (w trybie orbity, o ile kliknięcie nie trafiło w okno egui)
+                    glfw::WindowEvent::MouseButton(MouseButton::Button1, Action::Press, _)
+                        if self.camera.mode == CameraMode::Orbit
+                            && !self.gui.context().wants_pointer_input() =>
+                    {
+                        click = true;
+                    }
+                    glfw::WindowEvent::MouseButton(MouseButton::Button1, Action::Release, _) => {
+                        release = true;
                     }
                     _ => {}
                 }
             }
+            if click {
+                self.click();
+            }
+            if release {
+                self.gizmo.end_drag();
+            }
+            self.update_gizmo();
 
-            // 3. Input kamery – możesz rozważyć:
-            //    jeśli gui.ctx().wants_pointer_input() => nie ruszaj kamery
-            self.handle_input(dt);
+            // 3. Input kamery
+            self.handle_input(dt, focus);
+            animation::update(&mut self.world, dt);
 
-            let last_time = self.last_time;
-            let objects_count = self.objects.len();
+            let debug_info = self.debug_info();
+            let mut asset_action = None;
+            let mut file_action = None;
 
-            let full_output = self.gui.run(&self.window, current_time as f64, move |ctx| {
-                Engine::build_ui(ctx, last_time, objects_count);
+            // rozmiar w punktach egui jest uzupełniany przy rysowaniu
+            let viewport = self.viewport(Vector2::new(0.0, 0.0));
+            let shadow_settings = &mut self.shadow_settings;
+            let world = &mut self.world;
+            let camera = &mut self.camera;
+            let inspector = &mut self.inspector;
+            let gizmo = &mut self.gizmo;
+            let file_menu = &mut self.file_menu;
+            let full_output = self.gui.run(&self.window, current_time as f64, |ctx| {
+                // panel przed oknami, żeby nie zasłaniały go od góry
+                file_action = file_menu.show(ctx);
+                Engine::build_ui(
+                    ctx,
+                    &debug_info,
+                    &mut asset_action,
+                    shadow_settings,
+                    world,
+                    camera,
+                    inspector,
+                );
+
+                egui::Window::new("Gizmo").show(ctx, |ui| gizmo.settings_ui(ui));
+                // warstwa tła egui: nad sceną, pod oknami
+                let size = ctx.content_rect().size();
+                let viewport = Viewport {
+                    size: Vector2::new(size.x, size.y),
+                    ..viewport
+                };
+                let painter = ctx.layer_painter(egui::LayerId::background());
+                gizmo.paint(&painter, world, inspector.selected, &viewport);
             });
+            // trzyma uchwyty tekstur – inaczej zafałszowałby liczniki przy zwalnianiu
+            drop(debug_info);
+
+            // zaznaczenie z listy obiektów unieważnia ostatnie trafienie
+            if self
+                .hit
+                .is_some_and(|hit| self.inspector.selected != Some(hit.entity))
+            {
+                self.hit = None;
+            }
+
+            match asset_action {
+                Some(AssetAction::Unload(asset)) => {
+                    self.assets.unload(&asset);
+                }
+                Some(AssetAction::UnloadUnused) => self.assets.unload_unused(),
+                Some(AssetAction::LoadTexture(entity, path)) => self.load_texture(entity, &path),
+                None => {}
+            }
+            match file_action {
+                Some(FileAction::Save(path)) => {
+                    let result = scene::save_scene(
+                        &path,
+                        &self.world,
+                        &self.lights,
+                        self.ambient,
+                        &self.assets,
+                    );
+                    self.file_menu.saved(path, result);
+                }
+                Some(FileAction::Open(path)) => self.open_scene(path),
+                None => {}
+            }
 
             self.render(current_time);
 
             // 6. Render egui na wierzchu
-            self.gui.paint(&self.window, full_output);
+            self.gui.paint(&mut self.window, full_output);
 
             // 7. Swap buffers
             self.window.swap_buffers();
         }
     }
 
-    fn handle_input(&mut self, dt: f32) {
-        crate::input::process_input(&mut self.window, dt, &mut self.camera);
+    fn debug_info(&self) -> DebugInfo {
+        DebugInfo {
+            time: self.last_time,
+            objects_count: self.world.query::<&Renderable>().count(),
+            draw_calls: self.draw_calls.get(),
+            drawn: self.visibility.get().0,
+            culled: self.visibility.get().1,
+            lights_count: self.lights.len(),
+            materials: self.materials_usage(),
+            triangles_count: self
+                .world
+                .query::<&Renderable>()
+                .map(|(_, renderable)| renderable.mesh.index_count / 3)
+                .sum(),
+            mesh_counts: self.mesh_counts(),
+            resident: self.assets.resident(),
+            textures: self.assets.textures(),
+            hit: self.hit,
+            shader_errors: self
+                .shader_errors
+                .iter()
+                .map(|(label, log)| (label.clone(), log.clone()))
+                .collect(),
+            uniforms: self.program.uniforms(),
+            attributes: self.program.attributes(),
+        }
+    }
+
+    fn mesh_counts(&self) -> (i32, i32, i32) {
+        let mut meshes: Vec<&Rc<Mesh>> = Vec::new();
+        for (_, renderable) in self.world.query::<&Renderable>() {
+            if !meshes.iter().any(|m| Rc::ptr_eq(m, &renderable.mesh)) {
+                meshes.push(&renderable.mesh);
+            }
+        }
+        meshes
+            .iter()
+            .fold((0, 0, 0), |(vertices, unwelded, indices), mesh| {
+                (
+                    vertices + mesh.vertex_count,
+                    unwelded + mesh.unwelded_vertex_count,
+                    indices + mesh.index_count,
+                )
+            })
+    }
+
+    fn materials_usage(&self) -> Vec<(String, usize)> {
+        let mut materials: Vec<(&Rc<Material>, usize)> = Vec::new();
+        for (_, renderable) in self.world.query::<&Renderable>() {
+            match materials
+                .iter_mut()
+                .find(|(m, _)| Rc::ptr_eq(m, &renderable.material))
+            {
+                Some((_, users)) => *users += 1,
+                None => materials.push((&renderable.material, 1)),
+            }
+        }
+        materials
+            .into_iter()
+            .map(|(m, users)| (m.name.clone(), users))
+            .collect()
+    }
+
+    /// Podmienia teksturę koloru encji; materiał współdzielony jest kopiowany
+    fn load_texture(&mut self, entity: Entity, path: &str) {
+        let texture = self.assets.texture(path);
+        if let Some(renderable) = self.world.get_mut::<Renderable>(entity) {
+            Rc::make_mut(&mut renderable.material)
+                .textures
+                .insert(DIFFUSE_SLOT.to_string(), texture);
+        }
+    }
+
+    /// Przebudowuje shadery, których pliki zmieniły się na dysku
+    fn reload_shaders(&mut self) {
+        for (label, result) in self.assets.reload_programs() {
+            match result {
+                Ok(()) => {
+                    println!("Shader reloaded: {}", label);
+                    self.shader_errors.remove(&label);
+                }
+                Err(e) => {
+                    eprintln!("Shader reload failed ({}): {}", label, e);
+                    self.shader_errors.insert(label, e.to_string());
+                }
+            }
+        }
+    }
+
+    /// Kamera przeliczona na ekran o rozmiarze `size`
+    fn viewport(&self, size: Vector2<f32>) -> Viewport {
+        let (width, height) = self.window.get_framebuffer_size();
+        let aspect = width.max(1) as f32 / height.max(1) as f32;
+        Viewport {
+            view: self.camera.view_matrix(),
+            proj: self.camera.proj_matrix(aspect),
+            eye: self.camera.eye(),
+            size,
+        }
+    }
+
+    /// Widok i kursor w jednostkach okna (nie framebuffera)
+    fn cursor_viewport(&self) -> (Viewport, Vector2<f32>) {
+        let (width, height) = self.window.get_size();
+        let (x, y) = self.window.get_cursor_pos();
+        (
+            self.viewport(Vector2::new(width as f32, height as f32)),
+            Vector2::new(x as f32, y as f32),
+        )
+    }
+
+    /// Chwyta uchwyt gizmo, a poza nim zaznacza najbliższy obiekt pod kursorem
+    fn click(&mut self) {
+        let (viewport, cursor) = self.cursor_viewport();
+        let selected = self.inspector.selected;
+        if self
+            .gizmo
+            .begin_drag(&self.world, selected, &viewport, cursor)
+        {
+            return;
+        }
+
+        let ray = viewport.ray(cursor);
+        self.hit = ray.and_then(|ray| picking::pick(&self.world, &ray));
+        self.inspector.selected = self.hit.map(|hit| hit.entity);
+    }
+
+    /// Przeciąganie i podświetlanie uchwytów; kursor nad oknem egui nie
+    /// podświetla uchwytów, ale rozpoczęte przeciąganie trwa dalej
+    fn update_gizmo(&mut self) {
+        let (viewport, cursor) = self.cursor_viewport();
+        let over_gui = self.gui.context().is_pointer_over_area() && !self.gizmo.is_dragging();
+        let cursor = (self.camera.mode == CameraMode::Orbit && !over_gui).then_some(cursor);
+        let selected = self.inspector.selected;
+        self.gizmo
+            .update(&mut self.world, selected, &viewport, cursor);
+    }
+
+    fn handle_input(&mut self, dt: f32, focus: GuiFocus) {
+        input::process_input(
+            &mut self.window,
+            dt,
+            &mut self.camera,
+            &mut self.input,
+            focus,
+        );
+    }
+
+    /// Wejście zajęte przez egui: pole tekstowe z fokusem albo przeciąganie
+    /// w oknie. Samo najechanie na okno nie zatrzymuje rozglądania w trybie lotu.
+    fn gui_focus(&self) -> GuiFocus {
+        let ctx = self.gui.context();
+        GuiFocus {
+            keyboard: ctx.wants_keyboard_input(),
+            pointer: ctx.is_using_pointer(),
+        }
     }
 
     fn render(&mut self, time: f32) {
+        // okno zminimalizowane – nie ma gdzie rysować
+        let (width, height) = self.window.get_framebuffer_size();
+        if width <= 0 || height <= 0 {
+            return;
+        }
+        // egui_glow ustawia własny viewport przy rysowaniu
+        glcontext::set_viewport(width, height);
+
+        let resolution = self.shadow_settings.resolution;
+        if self
+            .shadow_map
+            .as_ref()
+            .is_some_and(|map| map.resolution != resolution)
+        {
+            self.shadow_map = None;
+            self.shadow_map = Engine::create_shadow_map(resolution);
+        }
+
+        self.scene_graph.update(&mut self.world, time);
+        self.render_scene(time, width, height);
+    }
+
+    /// Środek obszaru pokrytego mapą cieni – punkt, na który patrzy kamera
+    fn shadow_center(&self) -> Point3<f32> {
+        match self.camera.mode {
+            CameraMode::Orbit => self.camera.target,
+            CameraMode::FreeFly => {
+                self.camera.eye() + self.camera.forward() * self.shadow_settings.extent * 0.5
+            }
+        }
+    }
+
+    /// Renderuje głębokość sceny z pierwszego światła kierunkowego.
+    /// Zwraca indeks tego światła i jego macierz light space.
+    fn render_shadow_map(&self) -> Option<(usize, Matrix4<f32>)> {
+        if !self.shadow_settings.enabled {
+            return None;
+        }
+        let shadow_map = self.shadow_map.as_ref()?;
+        let (index, light) = self
+            .lights
+            .iter()
+            .take(MAX_LIGHTS)
+            .enumerate()
+            .find(|(_, light)| light.kind == LightKind::Directional)?;
+
+        let light_space = shadow::light_space_matrix(
+            light.direction,
+            self.shadow_center(),
+            &self.shadow_settings,
+        );
+
+        let mut previous_fbo = 0;
+        unsafe {
+            gl::GetIntegerv(gl::FRAMEBUFFER_BINDING, &mut previous_fbo);
+            gl::Enable(gl::DEPTH_TEST);
+            gl::DepthMask(gl::TRUE);
+        }
+
+        shadow_map.begin();
+        let program = &self.shadow_program;
+        program.use_program();
+        program.set_mat4("u_light_space", &light_space);
+
+        // obiekty przezroczyste nie rzucają cieni
+        let casters =
+            self.world
+                .query::<(&Renderable, &WorldTransform)>()
+                .filter(|(_, (renderable, _))| {
+                    let flags = renderable.material.flags;
+                    flags.cast_shadows && flags.mode != RenderMode::Blended
+                });
+        let items = casters.map(|(_, (renderable, model))| DrawItem {
+            renderable,
+            color_animation: None,
+            instance: InstanceData::new(model.0, renderable.tint),
+        });
+        for batch in instancing::build_batches(items, false) {
+            let material = batch.material;
+            let cutout = material
+                .texture(DIFFUSE_SLOT)
+                .filter(|_| material.flags.mode == RenderMode::Cutout);
+            match cutout {
+                Some(texture) => {
+                    texture.bind(0);
+                    program.set_int("u_alpha_cutout", 1);
+                }
+                None => program.set_int("u_alpha_cutout", 0),
+            }
+
+            self.instances.draw(batch.mesh, &batch.instances);
+        }
+
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, previous_fbo as u32);
+        }
+        Some((index, light_space))
+    }
+
+    /// Rysuje scenę do aktualnie związanego bufora ramki
+    fn render_scene(&self, time: f32, width: i32, height: i32) {
+        let shadow = self.render_shadow_map();
+        glcontext::set_viewport(width, height);
+
         unsafe {
             // Przywróć stan dla 3D
             gl::Enable(gl::DEPTH_TEST);
@@ -286,26 +683,348 @@ impl Engine {
             gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
         }
 
-        let (width, height) = self.window.get_size();
         let aspect = width as f32 / height as f32;
 
         let view = self.camera.view_matrix();
         let proj = self.camera.proj_matrix(aspect);
 
-        for obj in &self.objects {
-            obj.draw(&self.program, time, &view, &proj);
+        self.light_buffer.upload(&self.lights, self.ambient);
+
+        if let (Some(_), Some(shadow_map)) = (shadow, &self.shadow_map) {
+            shadow_map.bind_texture(SHADOW_MAP_UNIT);
+        }
+
+        // uniformy klatki ustawiamy raz w każdym programie używanym przez materiały
+        let mut programs: Vec<&Rc<Program>> = Vec::new();
+        for (_, renderable) in self.world.query::<&Renderable>() {
+            let program = &renderable.material.program;
+            if !programs.iter().any(|p| Rc::ptr_eq(p, program)) {
+                self.set_frame_uniforms(program, &view, &proj, shadow);
+                programs.push(program);
+            }
+        }
+
+        let frustum = Frustum::from_matrix(&(proj * view));
+        let queue = RenderQueue::build(
+            &self.world,
+            self.camera.eye(),
+            &frustum,
+            self.inspector.selected,
+        );
+        self.draw_batches(&queue.opaque, time);
+        self.draw_batches(&queue.cutout, time);
+
+        // przezroczyste na końcu: bez zapisu głębokości, od najdalszych
+        unsafe {
+            gl::Enable(gl::BLEND);
+            gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+            gl::DepthMask(gl::FALSE);
+        }
+        self.draw_batches(&queue.blended, time);
+        unsafe {
+            gl::DepthMask(gl::TRUE);
+            gl::Disable(gl::BLEND);
+        }
+
+        self.draw_calls.set(queue.draw_calls());
+        self.visibility.set((queue.drawn(), queue.culled));
+    }
+
+    fn draw_batches(&self, batches: &[Batch], time: f32) {
+        for batch in batches {
+            batch.material.apply();
+            if let Some(animation) = batch.single {
+                animation.apply(batch.material, time);
+            }
+            self.instances.draw(batch.mesh, &batch.instances);
+        }
+    }
+
+    fn set_frame_uniforms(
+        &self,
+        program: &Program,
+        view: &Matrix4<f32>,
+        proj: &Matrix4<f32>,
+        shadow: Option<(usize, Matrix4<f32>)>,
+    ) {
+        program.use_program();
+        program.set_mat4("u_view", view);
+        program.set_mat4("u_proj", proj);
+        if program.has_uniform("u_camera_pos") {
+            program.set_vec3("u_camera_pos", &self.camera.eye().to_vec());
+        }
+        // po przeładowaniu program traci przypisanie bloku
+        program.bind_uniform_block("Lights", LIGHTS_BINDING);
+
+        // sampler cieni zawsze na własnej jednostce – inaczej kolidowałby
+        // typem z teksturą materiału na jednostce 0
+        program.set_int("u_shadow_map", SHADOW_MAP_UNIT as i32);
+        match shadow {
+            Some((index, light_space)) => {
+                program.set_int("u_shadow_light", index as i32);
+                program.set_mat4("u_light_space", &light_space);
+                program.set_float("u_shadow_bias", self.shadow_settings.bias);
+            }
+            None => program.set_int("u_shadow_light", -1),
+        }
+    }
+
+    /// Renderuje aktualny widok do FBO w dowolnej rozdzielczości i zapisuje jako PNG
+    pub fn save_screenshot<P: AsRef<Path>>(
+        &self,
+        path: P,
+        width: i32,
+        height: i32,
+    ) -> Result<(), FramebufferError> {
+        let framebuffer = Framebuffer::new(width, height)?;
+        framebuffer.bind();
+        self.render_scene(self.glfw.get_time() as f32, width, height);
+        Framebuffer::unbind();
+
+        let (window_width, window_height) = self.window.get_framebuffer_size();
+        glcontext::set_viewport(window_width, window_height);
+
+        framebuffer.save_png(&path)?;
+        println!("Screenshot saved: {:?}", path.as_ref());
+        Ok(())
+    }
+
+    /// F12 – zrzut ekranu w rozdzielczości okna do katalogu screenshots/
+    fn take_screenshot(&self) {
+        if let Err(e) = fs::create_dir_all(SCREENSHOT_DIR) {
+            eprintln!("Failed to create {}: {}", SCREENSHOT_DIR, e);
+            return;
+        }
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let path = Path::new(SCREENSHOT_DIR).join(format!("screenshot-{}.png", timestamp));
+
+        let (width, height) = self.window.get_framebuffer_size();
+        if let Err(e) = self.save_screenshot(&path, width.max(1), height.max(1)) {
+            eprintln!("{}", e);
         }
     }
 
-    fn build_ui(ctx: &egui::Context, last_time: f32, objects_count: usize) {
+    fn build_ui(
+        ctx: &egui::Context,
+        info: &DebugInfo,
+        asset_action: &mut Option<AssetAction>,
+        shadows: &mut ShadowSettings,
+        world: &mut World,
+        camera: &mut Camera,
+        inspector: &mut Inspector,
+    ) {
         egui::Window::new("Debug").show(ctx, |ui| {
-            ui.label(format!("Time: {:.2}", last_time));
-            ui.label(format!("Objects: {}", objects_count));
+            ui.label(format!("Time: {:.2}", info.time));
+            ui.label(format!("Objects: {}", info.objects_count));
+            let [opaque, cutout, blended] = info.draw_calls;
+            ui.label(format!(
+                "Draw calls: {} (opaque {}, cutout {}, blended {})",
+                opaque + cutout + blended,
+                opaque,
+                cutout,
+                blended
+            ));
+            ui.label(format!("Drawn: {}, culled: {}", info.drawn, info.culled));
+            ui.label(format!("Lights: {}", info.lights_count));
+            ui.label(format!("Triangles: {}", info.triangles_count));
+            let (vertices, unwelded, indices) = info.mesh_counts;
+            ui.label(format!(
+                "Mesh vertices: {} (before welding {}), indices: {}",
+                vertices, unwelded, indices
+            ));
+
+            ui.collapsing(format!("Assets ({})", info.resident.len()), |ui| {
+                if ui.button("Unload unused").clicked() {
+                    *asset_action = Some(AssetAction::UnloadUnused);
+                }
+                for asset in &info.resident {
+                    ui.horizontal(|ui| {
+                        ui.label(format!(
+                            "{:?} {} ({} users)",
+                            asset.kind, asset.path, asset.users
+                        ));
+                        if ui.small_button("Unload").clicked() {
+                            *asset_action = Some(AssetAction::Unload(asset.clone()));
+                        }
+                    });
+                }
+            });
+
+            ui.collapsing(format!("Materials ({})", info.materials.len()), |ui| {
+                for (name, users) in &info.materials {
+                    ui.label(format!("{} ({} objects)", name, users));
+                }
+            });
+
+            ui.collapsing("Shader", |ui| {
+                ui.label("Uniforms:");
+                for uniform in &info.uniforms {
+                    ui.monospace(format!(
+                        "{:>3} {} {}{}",
+                        uniform.location,
+                        uniform.type_name(),
+                        uniform.name,
+                        if uniform.size > 1 {
+                            format!(" [{}]", uniform.size)
+                        } else {
+                            String::new()
+                        }
+                    ));
+                }
+                ui.label("Attributes:");
+                for attribute in &info.attributes {
+                    ui.monospace(format!(
+                        "{:>3} {} {}",
+                        attribute.location,
+                        attribute.type_name(),
+                        attribute.name
+                    ));
+                }
+            });
+        });
+
+        if !info.shader_errors.is_empty() {
+            egui::Window::new("Shader error").show(ctx, |ui| {
+                ui.label("Keeping the previous working programs.");
+                for (label, log) in &info.shader_errors {
+                    ui.separator();
+                    ui.strong(label);
+                    ui.monospace(log);
+                }
+            });
+        }
+
+        egui::Window::new("Shadows").show(ctx, |ui| {
+            ui.checkbox(&mut shadows.enabled, "Enabled");
+            egui::ComboBox::from_label("Resolution")
+                .selected_text(shadows.resolution.to_string())
+                .show_ui(ui, |ui| {
+                    for &resolution in SHADOW_RESOLUTIONS {
+                        ui.selectable_value(
+                            &mut shadows.resolution,
+                            resolution,
+                            resolution.to_string(),
+                        );
+                    }
+                });
+            ui.add(
+                egui::Slider::new(&mut shadows.bias, 0.0..=0.02)
+                    .text("Bias")
+                    .logarithmic(true),
+            );
+            ui.add(egui::Slider::new(&mut shadows.extent, 2.0..=50.0).text("Extent"));
         });
 
-        egui::Window::new("Camera").show(ctx, |ui| {
-            ui.label("Tu sobie później dodasz suwaki do kamery");
-            ui.label("(np. radius/yaw/pitch, zależnie co masz w Camera)");
+        egui::Window::new("Animations").show(ctx, |ui| {
+            let animated: Vec<Entity> = world.query::<&Animator>().map(|(e, _)| e).collect();
+            if animated.is_empty() {
+                ui.label("No animated objects");
+            }
+            for entity in animated {
+                let name = inspector::entity_name(world, entity);
+                if let Some(animator) = world.get_mut::<Animator>(entity) {
+                    ui.push_id(entity.index(), |ui| {
+                        Engine::animator_ui(ui, &name, animator)
+                    });
+                }
+            }
+        });
+
+        if let Some((entity, path)) = inspector.show(ctx, world, &info.textures) {
+            *asset_action = Some(AssetAction::LoadTexture(entity, path));
+        }
+
+        egui::Window::new("Picking").show(ctx, |ui| match info.hit {
+            Some(hit) => {
+                let name = inspector::entity_name(world, hit.entity);
+                ui.label(format!("Object: {} ({:?})", name, hit.entity));
+                let p = hit.position;
+                ui.label(format!("Position: ({:.2}, {:.2}, {:.2})", p.x, p.y, p.z));
+                let n = hit.normal;
+                ui.label(format!("Normal: ({:.2}, {:.2}, {:.2})", n.x, n.y, n.z));
+                ui.label(format!("Distance from camera: {:.2}", hit.distance));
+            }
+            None => {
+                ui.label("Left click an object to select it (orbit camera)");
+            }
+        });
+
+        egui::Window::new("Camera").show(ctx, |ui| Engine::camera_ui(ui, camera));
+    }
+
+    fn camera_ui(ui: &mut egui::Ui, camera: &mut Camera) {
+        use std::f32::consts::{PI, TAU};
+
+        ui.label(format!("Mode: {:?} (C to switch)", camera.mode));
+        ui.add_enabled_ui(camera.mode == CameraMode::Orbit, |ui| {
+            ui.add(egui::Slider::new(&mut camera.radius, MIN_RADIUS..=MAX_RADIUS).text("Radius"));
+            ui.add(
+                egui::Slider::new(&mut camera.theta, THETA_MARGIN..=PI - THETA_MARGIN)
+                    .text("Theta"),
+            );
+            camera.phi = camera.phi.rem_euclid(TAU);
+            ui.add(egui::Slider::new(&mut camera.phi, 0.0..=TAU).text("Phi"));
+        });
+        ui.add(egui::Slider::new(&mut camera.fov, 20.0..=100.0).text("FOV"));
+        let eye = camera.eye();
+        ui.label(format!("Eye: ({:.2}, {:.2}, {:.2})", eye.x, eye.y, eye.z));
+    }
+
+    fn animator_ui(ui: &mut egui::Ui, name: &str, animator: &mut Animator) {
+        ui.collapsing(name, |ui| {
+            let current = animator.current_index();
+            let mut selected = None;
+            egui::ComboBox::from_label("Clip")
+                .selected_text(animator.current().map_or("-", |clip| clip.name.as_str()))
+                .show_ui(ui, |ui| {
+                    for (index, clip) in animator.clips.iter().enumerate() {
+                        if ui.selectable_label(index == current, &clip.name).clicked() {
+                            selected = Some(index);
+                        }
+                    }
+                });
+            if let Some(index) = selected {
+                animator.play(index);
+            }
+
+            ui.horizontal(|ui| {
+                if animator.playing {
+                    if ui.button("Pause").clicked() {
+                        animator.pause();
+                    }
+                } else if ui.button("Play").clicked() {
+                    animator.resume();
+                }
+                if ui.button("Restart").clicked() {
+                    animator.play(current);
+                }
+            });
+
+            egui::ComboBox::from_label("Mode")
+                .selected_text(format!("{:?}", animator.mode))
+                .show_ui(ui, |ui| {
+                    for mode in [
+                        PlaybackMode::Loop,
+                        PlaybackMode::PingPong,
+                        PlaybackMode::Once,
+                    ] {
+                        ui.selectable_value(&mut animator.mode, mode, format!("{:?}", mode));
+                    }
+                });
+            ui.add(egui::Slider::new(&mut animator.speed, 0.0..=3.0).text("Speed"));
+
+            let duration = animator.current().map_or(0.0, AnimationClip::duration);
+            let mut time = animator.clip_time();
+            if ui
+                .add(egui::Slider::new(&mut time, 0.0..=duration).text("Time"))
+                .changed()
+            {
+                animator.seek(time);
+            }
         });
     }
 }
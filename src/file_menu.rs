@@ -0,0 +1,154 @@
+use std::path::{Path, PathBuf};
+
+use crate::scene::{self, SceneError};
+
+/// Polecenie z menu – wykonuje je silnik i oddaje wynik (`saved`, `opened`)
+pub enum FileAction {
+    Save(PathBuf),
+    Open(PathBuf),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Dialog {
+    SaveAs,
+    Open,
+}
+
+/// Górny pasek z menu "File": otwarcie sceny, zapis do jej pliku albo pod nową ścieżką
+pub struct FileMenu {
+    /// Plik bieżącej sceny
+    path: PathBuf,
+    /// Plik, do którego zapisuje "Save"; `None` dla sceny domyślnej –
+    /// wtedy "Save" działa jak "Save As"
+    target: Option<PathBuf>,
+    /// Okno ze ścieżką do wpisania; `None` – okno zamknięte
+    dialog: Option<(Dialog, String)>,
+    /// Wynik ostatniego zapisu albo otwarcia pokazywany na pasku
+    status: Option<String>,
+}
+
+impl FileMenu {
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        let path = path.as_ref().to_path_buf();
+        FileMenu {
+            target: save_target(&path),
+            path,
+            dialog: None,
+            status: None,
+        }
+    }
+
+    /// Rysuje pasek i okna "Save As"/"Open". Zwraca polecenie do wykonania
+    pub fn show(&mut self, ctx: &egui::Context) -> Option<FileAction> {
+        let mut action = None;
+
+        egui::TopBottomPanel::top("menu_bar").show(ctx, |ui| {
+            egui::MenuBar::new().ui(ui, |ui| {
+                ui.menu_button("File", |ui| {
+                    if ui.button("Open…").clicked() {
+                        self.dialog = Some((Dialog::Open, self.path.display().to_string()));
+                    }
+                    if ui.button("Save").clicked() {
+                        match &self.target {
+                            Some(target) => action = Some(FileAction::Save(target.clone())),
+                            None => self.dialog = Some((Dialog::SaveAs, String::new())),
+                        }
+                    }
+                    if ui.button("Save As…").clicked() {
+                        let path = match &self.target {
+                            Some(target) => target.display().to_string(),
+                            None => String::new(),
+                        };
+                        self.dialog = Some((Dialog::SaveAs, path));
+                    }
+                });
+                ui.separator();
+                match &self.target {
+                    Some(_) => ui.label(self.path.display().to_string()),
+                    None => ui.label(format!("{} (read-only)", self.path.display())),
+                };
+                if let Some(status) = &self.status {
+                    ui.separator();
+                    ui.label(status);
+                }
+            });
+        });
+
+        let mut open = self.dialog.is_some();
+        let mut close = false;
+        let mut refused = None;
+        if let Some((dialog, path)) = &mut self.dialog {
+            let (title, button) = match dialog {
+                Dialog::SaveAs => ("Save As", "Save"),
+                Dialog::Open => ("Open", "Open"),
+            };
+            egui::Window::new(title)
+                .open(&mut open)
+                .collapsible(false)
+                .show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Path:");
+                        ui.text_edit_singleline(path);
+                    });
+                    if ui.button(button).clicked() && !path.is_empty() {
+                        let path = PathBuf::from(path.as_str());
+                        match dialog {
+                            Dialog::SaveAs if save_target(&path).is_none() => {
+                                refused = Some(path);
+                                return;
+                            }
+                            Dialog::SaveAs => action = Some(FileAction::Save(path)),
+                            Dialog::Open => action = Some(FileAction::Open(path)),
+                        }
+                        close = true;
+                    }
+                });
+        }
+        if let Some(path) = refused {
+            self.status = Some(format!(
+                "{} is the built-in scene, choose another path",
+                path.display()
+            ));
+        }
+        if !open || close {
+            self.dialog = None;
+        }
+
+        action
+    }
+
+    /// Wynik zapisu; udany zapis zmienia plik bieżącej sceny
+    pub fn saved(&mut self, path: PathBuf, result: Result<(), SceneError>) {
+        match result {
+            Ok(()) => {
+                self.status = Some(format!("Saved {}", path.display()));
+                self.target = Some(path.clone());
+                self.path = path;
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                self.status = Some(format!("Save failed: {}", e));
+            }
+        }
+    }
+
+    /// Wynik otwarcia; przy błędzie zostaje poprzednia scena
+    pub fn opened(&mut self, path: PathBuf, result: Result<(), SceneError>) {
+        match result {
+            Ok(()) => {
+                self.status = Some(format!("Opened {}", path.display()));
+                self.target = save_target(&path);
+                self.path = path;
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                self.status = Some(format!("Open failed: {}", e));
+            }
+        }
+    }
+}
+
+/// Scena domyślna jest częścią projektu – nie nadpisujemy jej
+fn save_target(path: &Path) -> Option<PathBuf> {
+    (!scene::is_default_scene(path)).then(|| path.to_path_buf())
+}
@@ -0,0 +1,165 @@
+use std::fmt;
+use std::io;
+use std::os::raw::c_void;
+use std::path::{Path, PathBuf};
+use std::ptr;
+
+#[derive(Debug)]
+pub enum FramebufferError {
+    /// glCheckFramebufferStatus zwrócił coś innego niż FRAMEBUFFER_COMPLETE
+    Incomplete(u32),
+    Save {
+        path: PathBuf,
+        source: io::Error,
+    },
+}
+
+impl fmt::Display for FramebufferError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FramebufferError::Incomplete(status) => {
+                write!(f, "framebuffer is incomplete (status 0x{:X})", status)
+            }
+            FramebufferError::Save { path, source } => {
+                write!(f, "failed to save image {:?}: {}", path, source)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FramebufferError {}
+
+/// Bufor ramki poza ekranem: kolor RGBA8 w teksturze + głębia w renderbufferze
+pub struct Framebuffer {
+    pub fbo: u32,
+    pub color: u32,
+    pub depth: u32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Framebuffer {
+    pub fn new(width: i32, height: i32) -> Result<Framebuffer, FramebufferError> {
+        let mut fbo = 0;
+        let mut color = 0;
+        let mut depth = 0;
+
+        let status = unsafe {
+            gl::GenFramebuffers(1, &mut fbo);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, fbo);
+
+            gl::GenTextures(1, &mut color);
+            gl::BindTexture(gl::TEXTURE_2D, color);
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                gl::RGBA8 as i32,
+                width,
+                height,
+                0,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                ptr::null(),
+            );
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+            gl::FramebufferTexture2D(
+                gl::FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT0,
+                gl::TEXTURE_2D,
+                color,
+                0,
+            );
+
+            gl::GenRenderbuffers(1, &mut depth);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, depth);
+            gl::RenderbufferStorage(gl::RENDERBUFFER, gl::DEPTH24_STENCIL8, width, height);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, 0);
+            gl::FramebufferRenderbuffer(
+                gl::FRAMEBUFFER,
+                gl::DEPTH_STENCIL_ATTACHMENT,
+                gl::RENDERBUFFER,
+                depth,
+            );
+
+            let status = gl::CheckFramebufferStatus(gl::FRAMEBUFFER);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            status
+        };
+
+        let framebuffer = Framebuffer {
+            fbo,
+            color,
+            depth,
+            width,
+            height,
+        };
+
+        if status != gl::FRAMEBUFFER_COMPLETE {
+            return Err(FramebufferError::Incomplete(status));
+        }
+        Ok(framebuffer)
+    }
+
+    pub fn bind(&self) {
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.fbo);
+            gl::Viewport(0, 0, self.width, self.height);
+        }
+    }
+
+    /// Wraca do domyślnego bufora okna (viewport trzeba ustawić samemu)
+    pub fn unbind() {
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+    }
+
+    /// Odczytuje kolor do obrazu RGBA (wiersze odwrócone: (0,0) w lewym górnym rogu)
+    pub fn read_pixels(&self) -> image::RgbaImage {
+        let row = self.width as usize * 4;
+        let mut data = vec![0u8; row * self.height as usize];
+
+        unsafe {
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.fbo);
+            gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+            gl::ReadPixels(
+                0,
+                0,
+                self.width,
+                self.height,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                data.as_mut_ptr() as *mut c_void,
+            );
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, 0);
+        }
+
+        // OpenGL ma (0,0) w lewym dolnym rogu
+        let flipped: Vec<u8> = data.chunks_exact(row).rev().flatten().copied().collect();
+
+        image::RgbaImage::from_raw(self.width as u32, self.height as u32, flipped)
+            .expect("Pixel buffer size matches the framebuffer")
+    }
+
+    pub fn save_png<P: AsRef<Path>>(&self, path: P) -> Result<(), FramebufferError> {
+        let path = path.as_ref();
+        self.read_pixels()
+            .save(path)
+            .map_err(|source| FramebufferError::Save {
+                path: path.to_path_buf(),
+                source,
+            })
+    }
+}
+
+impl Drop for Framebuffer {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteFramebuffers(1, &self.fbo);
+            gl::DeleteTextures(1, &self.color);
+            gl::DeleteRenderbuffers(1, &self.depth);
+        }
+    }
+}
@@ -0,0 +1,602 @@
+use cgmath::{
+    EuclideanSpace, InnerSpace, Matrix4, Point3, Quaternion, Rad, Rotation3, SquareMatrix,
+    Transform as _, Vector2, Vector3, Zero,
+};
+use egui::{Color32, Pos2, Rect, Stroke, vec2};
+
+use crate::ecs::{Entity, World};
+use crate::picking::Ray;
+use crate::scene_graph::{Parent, Transform, WorldTransform};
+
+/// Długość osi gizmo jako ułamek odległości od kamery – stały rozmiar na ekranie
+const GIZMO_SCALE: f32 = 0.15;
+
+/// Odległość kursora (w jednostkach ekranu), z której łapie się uchwyt
+const PICK_RADIUS: f32 = 8.0;
+
+const RING_SEGMENTS: usize = 64;
+
+/// Pierścienie, których płaszczyzna jest prawie równoległa do kierunku patrzenia,
+/// nie dają się chwycić (cosinus kąta między osią a kierunkiem patrzenia)
+const EDGE_ON_RING: f32 = 0.1;
+
+/// Ruch kursora w górę, który podwaja skalę przy skalowaniu jednolitym
+const UNIFORM_SCALE_PIXELS: f32 = 100.0;
+
+/// Najmniejszy mnożnik skali – zerowa skala psułaby macierz świata
+const MIN_SCALE_FACTOR: f32 = 0.01;
+
+const LINE_WIDTH: f32 = 3.0;
+const AXIS_COLORS: [Color32; 3] = [
+    Color32::from_rgb(230, 60, 60),
+    Color32::from_rgb(80, 200, 80),
+    Color32::from_rgb(70, 110, 240),
+];
+const UNIFORM_COLOR: Color32 = Color32::from_rgb(220, 220, 220);
+const ACTIVE_COLOR: Color32 = Color32::from_rgb(255, 210, 0);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GizmoMode {
+    Translate,
+    Rotate,
+    /// Zawsze wzdłuż osi obiektu
+    Scale,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GizmoSpace {
+    World,
+    Local,
+}
+
+/// Uchwyt gizmo; indeks to oś (0 = X, 1 = Y, 2 = Z)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Handle {
+    Translate(usize),
+    /// Przesuwanie w płaszczyźnie prostopadłej do osi
+    TranslatePlane(usize),
+    /// Pierścień obrotu wokół osi
+    Rotate(usize),
+    Scale(usize),
+    ScaleUniform,
+}
+
+impl Handle {
+    fn color(self) -> Color32 {
+        match self {
+            Handle::Translate(axis)
+            | Handle::TranslatePlane(axis)
+            | Handle::Rotate(axis)
+            | Handle::Scale(axis) => AXIS_COLORS[axis],
+            Handle::ScaleUniform => UNIFORM_COLOR,
+        }
+    }
+}
+
+/// Kamera przeliczona na ekran o rozmiarze `size` – w jednostkach okna
+/// (jak pozycja kursora z GLFW) albo w punktach egui przy rysowaniu
+#[derive(Debug, Clone, Copy)]
+pub struct Viewport {
+    pub view: Matrix4<f32>,
+    pub proj: Matrix4<f32>,
+    pub eye: Point3<f32>,
+    pub size: Vector2<f32>,
+}
+
+impl Viewport {
+    /// Punkt świata na ekranie, (0, 0) w lewym górnym rogu; `None` za kamerą
+    pub fn project(&self, point: Point3<f32>) -> Option<Vector2<f32>> {
+        let clip = self.proj * self.view * point.to_homogeneous();
+        if clip.w <= 1e-4 {
+            return None;
+        }
+        let ndc = clip.truncate() / clip.w;
+        Some(Vector2::new(
+            (ndc.x + 1.0) * 0.5 * self.size.x,
+            (1.0 - ndc.y) * 0.5 * self.size.y,
+        ))
+    }
+
+    pub fn ray(&self, cursor: Vector2<f32>) -> Option<Ray> {
+        Ray::from_cursor(
+            (cursor.x as f64, cursor.y as f64),
+            (self.size.x as i32, self.size.y as i32),
+            &self.view,
+            &self.proj,
+        )
+    }
+}
+
+/// Położenie, osie i rozmiar gizmo w świecie
+#[derive(Debug, Clone, Copy)]
+struct Frame {
+    origin: Point3<f32>,
+    axes: [Vector3<f32>; 3],
+    size: f32,
+}
+
+/// Uchwyt do narysowania i trafienia kursorem
+enum Shape {
+    /// Łamana (jeden punkt – sam znacznik) ze znacznikiem na końcu
+    Line(Vec<Point3<f32>>, Tip),
+    Quad([Point3<f32>; 4]),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tip {
+    None,
+    Arrow,
+    Box,
+}
+
+/// Trwające przeciąganie uchwytu
+struct Drag {
+    handle: Handle,
+    entity: Entity,
+    space: GizmoSpace,
+    /// `Transform` encji w chwili chwycenia – zmiana liczona jest od niego
+    start: Transform,
+    frame: Frame,
+    /// Odwrotność macierzy świata rodzica – przelicza zmiany do przestrzeni `Transform`
+    parent_inverse: Matrix4<f32>,
+    /// Punkt chwycenia na osi, płaszczyźnie albo pierścieniu
+    anchor: Point3<f32>,
+    cursor: Vector2<f32>,
+}
+
+/// Uchwyty do przesuwania, obracania i skalowania zaznaczonej encji.
+/// Rysowane przez egui na wierzchu sceny, sterowane myszą spoza okien egui.
+pub struct Gizmo {
+    pub mode: GizmoMode,
+    pub space: GizmoSpace,
+    pub snap: bool,
+    /// Krok siatki przesunięcia
+    pub grid: f32,
+    /// Krok kąta obrotu w stopniach
+    pub angle_step: f32,
+    /// Krok mnożnika skali
+    pub scale_step: f32,
+    hovered: Option<Handle>,
+    drag: Option<Drag>,
+}
+
+impl Default for Gizmo {
+    fn default() -> Self {
+        Gizmo {
+            mode: GizmoMode::Translate,
+            space: GizmoSpace::World,
+            snap: false,
+            grid: 0.5,
+            angle_step: 15.0,
+            scale_step: 0.1,
+            hovered: None,
+            drag: None,
+        }
+    }
+}
+
+impl Gizmo {
+    pub fn new() -> Self {
+        Gizmo::default()
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        self.drag.is_some()
+    }
+
+    /// Chwyta uchwyt pod kursorem; `false`, gdy kursor nie trafił w gizmo
+    pub fn begin_drag(
+        &mut self,
+        world: &World,
+        selected: Option<Entity>,
+        viewport: &Viewport,
+        cursor: Vector2<f32>,
+    ) -> bool {
+        self.drag = selected.and_then(|entity| self.start_drag(world, entity, viewport, cursor));
+        self.hovered = self.drag.as_ref().map(|drag| drag.handle);
+        self.drag.is_some()
+    }
+
+    pub fn end_drag(&mut self) {
+        self.drag = None;
+    }
+
+    /// Co klatkę: przeciąga chwycony uchwyt albo podświetla ten pod kursorem.
+    /// `cursor` = `None`, gdy mysz należy do egui.
+    pub fn update(
+        &mut self,
+        world: &mut World,
+        selected: Option<Entity>,
+        viewport: &Viewport,
+        cursor: Option<Vector2<f32>>,
+    ) {
+        if self.drag.is_some() {
+            if let Some(cursor) = cursor {
+                self.drag_to(world, viewport, cursor);
+            }
+            return;
+        }
+
+        self.hovered = match (selected, cursor) {
+            (Some(entity), Some(cursor)) => self
+                .frame(world, entity, viewport)
+                .and_then(|frame| self.hit(&frame, viewport, cursor)),
+            _ => None,
+        };
+    }
+
+    pub fn paint(
+        &self,
+        painter: &egui::Painter,
+        world: &World,
+        selected: Option<Entity>,
+        viewport: &Viewport,
+    ) {
+        let Some(frame) = selected.and_then(|entity| self.frame(world, entity, viewport)) else {
+            return;
+        };
+        let active = self.drag.as_ref().map(|drag| drag.handle).or(self.hovered);
+
+        for (handle, shape) in self.shapes(&frame) {
+            let color = if active == Some(handle) {
+                ACTIVE_COLOR
+            } else {
+                handle.color()
+            };
+            match shape {
+                Shape::Quad(corners) => {
+                    let Some(points) = project_all(viewport, &corners) else {
+                        continue;
+                    };
+                    painter.add(egui::Shape::convex_polygon(
+                        points.into_iter().map(to_pos).collect(),
+                        color.gamma_multiply(0.4),
+                        Stroke::new(1.0, color),
+                    ));
+                }
+                Shape::Line(points, tip) => {
+                    let Some(points) = project_all(viewport, &points) else {
+                        continue;
+                    };
+                    paint_line(painter, &points, tip, color);
+                }
+            }
+        }
+    }
+
+    /// Okno z trybem, przestrzenią i przyciąganiem
+    pub fn settings_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            for mode in [GizmoMode::Translate, GizmoMode::Rotate, GizmoMode::Scale] {
+                ui.selectable_value(&mut self.mode, mode, format!("{:?}", mode));
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Space:");
+            ui.selectable_value(&mut self.space, GizmoSpace::World, "World");
+            ui.selectable_value(&mut self.space, GizmoSpace::Local, "Local");
+        });
+        if self.mode == GizmoMode::Scale {
+            ui.label("Scale always uses the object's axes");
+        }
+
+        ui.checkbox(&mut self.snap, "Snap");
+        ui.add_enabled_ui(self.snap, |ui| {
+            ui.add(egui::Slider::new(&mut self.grid, 0.05..=5.0).text("Grid"));
+            ui.add(egui::Slider::new(&mut self.angle_step, 1.0..=90.0).text("Angle (deg)"));
+            ui.add(egui::Slider::new(&mut self.scale_step, 0.01..=1.0).text("Scale step"));
+        });
+    }
+
+    fn frame(&self, world: &World, entity: Entity, viewport: &Viewport) -> Option<Frame> {
+        // gizmo zmienia `Transform`, bez niego nie ma czego przesuwać
+        if !world.has::<Transform>(entity) {
+            return None;
+        }
+        let WorldTransform(model) = world.get::<WorldTransform>(entity)?;
+        let origin = Point3::from_vec(model.w.truncate());
+
+        let world_axes = [Vector3::unit_x(), Vector3::unit_y(), Vector3::unit_z()];
+        let local = self.space == GizmoSpace::Local || self.mode == GizmoMode::Scale;
+        let local_axes = [model.x, model.y, model.z].map(|column| column.truncate());
+        let axes = if local && local_axes.iter().all(|axis| axis.magnitude2() > 1e-8) {
+            local_axes.map(InnerSpace::normalize)
+        } else {
+            world_axes
+        };
+
+        Some(Frame {
+            origin,
+            axes,
+            size: (origin - viewport.eye).magnitude() * GIZMO_SCALE,
+        })
+    }
+
+    fn shapes(&self, frame: &Frame) -> Vec<(Handle, Shape)> {
+        let Frame { origin, axes, size } = *frame;
+        let plane = |axis: usize| (axes[(axis + 1) % 3] * size, axes[(axis + 2) % 3] * size);
+        let mut shapes = Vec::new();
+
+        match self.mode {
+            GizmoMode::Translate => {
+                for (axis, &direction) in axes.iter().enumerate() {
+                    let points = vec![origin, origin + direction * size];
+                    shapes.push((Handle::Translate(axis), Shape::Line(points, Tip::Arrow)));
+                }
+                for axis in 0..3 {
+                    let (a, b) = plane(axis);
+                    let (near, far) = (0.25, 0.45);
+                    let corners = [
+                        origin + a * near + b * near,
+                        origin + a * far + b * near,
+                        origin + a * far + b * far,
+                        origin + a * near + b * far,
+                    ];
+                    shapes.push((Handle::TranslatePlane(axis), Shape::Quad(corners)));
+                }
+            }
+            GizmoMode::Rotate => {
+                for axis in 0..3 {
+                    let (a, b) = plane(axis);
+                    let points = (0..=RING_SEGMENTS)
+                        .map(|i| {
+                            let angle = i as f32 / RING_SEGMENTS as f32 * std::f32::consts::TAU;
+                            origin + a * angle.cos() + b * angle.sin()
+                        })
+                        .collect();
+                    shapes.push((Handle::Rotate(axis), Shape::Line(points, Tip::None)));
+                }
+            }
+            GizmoMode::Scale => {
+                // środek pierwszy – ma pierwszeństwo przed początkami osi
+                shapes.push((Handle::ScaleUniform, Shape::Line(vec![origin], Tip::Box)));
+                for (axis, &direction) in axes.iter().enumerate() {
+                    let points = vec![origin, origin + direction * size];
+                    shapes.push((Handle::Scale(axis), Shape::Line(points, Tip::Box)));
+                }
+            }
+        }
+        shapes
+    }
+
+    /// Uchwyt pod kursorem: płaszczyzny i środek, jeśli kursor jest w nich,
+    /// inaczej najbliższa oś albo pierścień w promieniu `PICK_RADIUS`
+    fn hit(&self, frame: &Frame, viewport: &Viewport, cursor: Vector2<f32>) -> Option<Handle> {
+        let mut best = None;
+        let mut best_distance = PICK_RADIUS;
+
+        let view_direction = (frame.origin - viewport.eye).normalize();
+        for (handle, shape) in self.shapes(frame) {
+            // pierścień widziany z boku to odcinek – kąt obrotu byłby nieokreślony
+            if let Handle::Rotate(axis) = handle
+                && frame.axes[axis].dot(view_direction).abs() < EDGE_ON_RING
+            {
+                continue;
+            }
+            let distance = match shape {
+                Shape::Quad(corners) => match project_all(viewport, &corners) {
+                    Some(points) if inside_convex(&points, cursor) => 0.0,
+                    _ => continue,
+                },
+                Shape::Line(points, _) => {
+                    let Some(points) = project_all(viewport, &points) else {
+                        continue;
+                    };
+                    match points.as_slice() {
+                        [point] if (cursor - point).magnitude() < PICK_RADIUS => 0.0,
+                        [_] => continue,
+                        _ => points
+                            .windows(2)
+                            .map(|segment| segment_distance(cursor, segment[0], segment[1]))
+                            .fold(f32::INFINITY, f32::min),
+                    }
+                }
+            };
+            if distance < best_distance {
+                best = Some(handle);
+                best_distance = distance;
+            }
+        }
+        best
+    }
+
+    fn start_drag(
+        &self,
+        world: &World,
+        entity: Entity,
+        viewport: &Viewport,
+        cursor: Vector2<f32>,
+    ) -> Option<Drag> {
+        let frame = self.frame(world, entity, viewport)?;
+        let handle = self.hit(&frame, viewport, cursor)?;
+        let ray = viewport.ray(cursor)?;
+
+        let anchor = match handle {
+            Handle::Translate(axis) | Handle::Scale(axis) => {
+                let t = ray.closest_on_line(frame.origin, frame.axes[axis])?;
+                // chwyt tuż przy środku dawałby skokową skalę
+                if matches!(handle, Handle::Scale(_)) && t.abs() < frame.size * 0.05 {
+                    return None;
+                }
+                frame.origin + frame.axes[axis] * t
+            }
+            Handle::TranslatePlane(axis) | Handle::Rotate(axis) => {
+                ray.intersect_plane(frame.origin, frame.axes[axis])?
+            }
+            Handle::ScaleUniform => frame.origin,
+        };
+
+        let parent_inverse = world
+            .get::<Parent>(entity)
+            .and_then(|parent| world.get::<WorldTransform>(parent.0))
+            .and_then(|parent| parent.0.invert())
+            .unwrap_or_else(Matrix4::identity);
+
+        Some(Drag {
+            handle,
+            entity,
+            space: self.space,
+            start: *world.get::<Transform>(entity)?,
+            frame,
+            parent_inverse,
+            anchor,
+            cursor,
+        })
+    }
+
+    fn drag_to(&mut self, world: &mut World, viewport: &Viewport, cursor: Vector2<f32>) {
+        let Some(drag) = &self.drag else {
+            return;
+        };
+        let Some(ray) = viewport.ray(cursor) else {
+            return;
+        };
+        let Frame { origin, axes, .. } = drag.frame;
+        let mut transform = drag.start;
+
+        match drag.handle {
+            Handle::Translate(axis) => {
+                let Some(t) = ray.closest_on_line(origin, axes[axis]) else {
+                    return;
+                };
+                let start = (drag.anchor - origin).dot(axes[axis]);
+                let delta = axes[axis] * self.snapped(t - start, self.grid);
+                transform.translation += drag.parent_inverse.transform_vector(delta);
+            }
+            Handle::TranslatePlane(axis) => {
+                let Some(point) = ray.intersect_plane(origin, axes[axis]) else {
+                    return;
+                };
+                let moved = point - drag.anchor;
+                let mut delta = Vector3::zero();
+                for direction in [axes[(axis + 1) % 3], axes[(axis + 2) % 3]] {
+                    delta += direction * self.snapped(moved.dot(direction), self.grid);
+                }
+                transform.translation += drag.parent_inverse.transform_vector(delta);
+            }
+            Handle::Rotate(axis) => {
+                let Some(point) = ray.intersect_plane(origin, axes[axis]) else {
+                    return;
+                };
+                let (from, to) = (drag.anchor - origin, point - origin);
+                let angle = axes[axis].dot(from.cross(to)).atan2(from.dot(to));
+                let angle = Rad(self
+                    .snapped(angle.to_degrees(), self.angle_step)
+                    .to_radians());
+
+                let rotation = match drag.space {
+                    // oś świata przeliczona do przestrzeni rodzica, obrót przed dotychczasowym
+                    GizmoSpace::World => {
+                        let axis = drag.parent_inverse.transform_vector(axes[axis]);
+                        Quaternion::from_axis_angle(axis.normalize(), angle) * drag.start.rotation
+                    }
+                    GizmoSpace::Local => {
+                        let mut local_axis = Vector3::zero();
+                        local_axis[axis] = 1.0;
+                        drag.start.rotation * Quaternion::from_axis_angle(local_axis, angle)
+                    }
+                };
+                transform.rotation = rotation.normalize();
+            }
+            Handle::Scale(axis) => {
+                let Some(t) = ray.closest_on_line(origin, axes[axis]) else {
+                    return;
+                };
+                let start = (drag.anchor - origin).dot(axes[axis]);
+                let factor = self.snapped(t / start, self.scale_step);
+                transform.scale[axis] *= factor.max(MIN_SCALE_FACTOR);
+            }
+            Handle::ScaleUniform => {
+                let factor = 2.0f32.powf((drag.cursor.y - cursor.y) / UNIFORM_SCALE_PIXELS);
+                let factor = self.snapped(factor, self.scale_step);
+                transform.scale *= factor.max(MIN_SCALE_FACTOR);
+            }
+        }
+
+        world.insert(drag.entity, transform);
+    }
+
+    fn snapped(&self, value: f32, step: f32) -> f32 {
+        if self.snap && step > 0.0 {
+            (value / step).round() * step
+        } else {
+            value
+        }
+    }
+}
+
+fn project_all(viewport: &Viewport, points: &[Point3<f32>]) -> Option<Vec<Vector2<f32>>> {
+    points
+        .iter()
+        .map(|&point| viewport.project(point))
+        .collect()
+}
+
+fn to_pos(point: Vector2<f32>) -> Pos2 {
+    Pos2::new(point.x, point.y)
+}
+
+fn paint_line(painter: &egui::Painter, points: &[Vector2<f32>], tip: Tip, color: Color32) {
+    if points.len() > 1 {
+        let line = points.iter().copied().map(to_pos).collect();
+        painter.line(line, Stroke::new(LINE_WIDTH, color));
+    }
+    let Some(&end) = points.last() else {
+        return;
+    };
+
+    match tip {
+        Tip::None => {}
+        Tip::Box => {
+            let rect = Rect::from_center_size(to_pos(end), vec2(10.0, 10.0));
+            painter.rect_filled(rect, 0.0, color);
+        }
+        Tip::Arrow => {
+            let Some(&before) = points.iter().rev().nth(1) else {
+                return;
+            };
+            let direction = end - before;
+            if direction.magnitude2() < 1e-6 {
+                return;
+            }
+            let direction = direction.normalize();
+            let side = Vector2::new(-direction.y, direction.x);
+            let back = end - direction * 14.0;
+            let head = vec![
+                to_pos(end + direction * 4.0),
+                to_pos(back + side * 6.0),
+                to_pos(back - side * 6.0),
+            ];
+            painter.add(egui::Shape::convex_polygon(head, color, Stroke::NONE));
+        }
+    }
+}
+
+fn segment_distance(point: Vector2<f32>, a: Vector2<f32>, b: Vector2<f32>) -> f32 {
+    let ab = b - a;
+    let length2 = ab.magnitude2();
+    let t = if length2 > 0.0 {
+        ((point - a).dot(ab) / length2).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    (point - (a + ab * t)).magnitude()
+}
+
+/// Czy punkt leży w wypukłym wielokącie (dowolny obieg wierzchołków)
+fn inside_convex(points: &[Vector2<f32>], point: Vector2<f32>) -> bool {
+    let mut sign = 0.0f32;
+    for (i, &a) in points.iter().enumerate() {
+        let b = points[(i + 1) % points.len()];
+        let edge = b - a;
+        let to_point = point - a;
+        let cross = edge.x * to_point.y - edge.y * to_point.x;
+        if cross != 0.0 {
+            if sign != 0.0 && cross.signum() != sign {
+                return false;
+            }
+            sign = cross.signum();
+        }
+    }
+    true
+}
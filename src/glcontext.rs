@@ -22,11 +22,38 @@ pub fn create_window(
         .create_window(width, height, title, WindowMode::Windowed)
         .expect("Failed to create GLFW window");
     window.set_key_polling(true);
+    window.set_cursor_pos_polling(true);
+    window.set_mouse_button_polling(true);
+    // znaki i kółko myszy dla egui
+    window.set_char_polling(true);
+    window.set_scroll_polling(true);
+    window.set_framebuffer_size_polling(true);
+    window.set_content_scale_polling(true);
     (window, events)
 }
 
+/// Ukryte okno – daje tylko kontekst GL do renderowania poza ekranem.
+/// Nadal wymaga serwera X; bez wyświetlacza (i GPU) obsługiwana droga to
+/// `LIBGL_ALWAYS_SOFTWARE=1 xvfb-run -a cargo run -- --screenshot out.png`
+pub fn create_hidden_window(
+    glfw: &mut glfw::Glfw,
+    width: u32,
+    height: u32,
+    title: &str,
+) -> (glfw::Window, Receiver<(f64, glfw::WindowEvent)>) {
+    glfw.window_hint(WindowHint::Visible(false));
+    let result = create_window(glfw, width, height, title);
+    glfw.window_hint(WindowHint::Visible(true));
+    result
+}
+
 pub fn init_gl(window: &mut glfw::Window) {
     gl::load_with(|symbol| window.get_proc_address(symbol) as *const _);
+
+    // framebuffer może być większy niż okno (HiDPI)
+    let (width, height) = window.get_framebuffer_size();
+    set_viewport(width, height);
+
     unsafe {
         gl::Enable(gl::DEPTH_TEST);
         gl::ClearColor(0.2, 0.3, 0.4, 1.0);
@@ -35,3 +62,9 @@ pub fn init_gl(window: &mut glfw::Window) {
         gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
     }
 }
+
+pub fn set_viewport(width: i32, height: i32) {
+    unsafe {
+        gl::Viewport(0, 0, width, height);
+    }
+}
@@ -8,6 +8,8 @@ pub struct Gui {
     painter: Painter,
     events: Vec<EguiEvent>,
     pointer_pos: Option<Pos2>,
+    /// Kursor ustawiony ostatnio w oknie – zmieniamy go tylko przy zmianie
+    cursor_icon: egui::CursorIcon,
 }
 
 impl Gui {
@@ -22,6 +24,7 @@ impl Gui {
             painter,
             events: Vec::new(),
             pointer_pos: None,
+            cursor_icon: egui::CursorIcon::Default,
         }
     }
 
@@ -38,11 +41,14 @@ impl Gui {
     pub fn on_glfw_event(&mut self, window: &glfw::Window, event: &glfw::WindowEvent) {
         use egui::{Event, PointerButton};
         use glfw::WindowEvent::*;
-        use glfw::{Action, Modifiers, MouseButton};
+        use glfw::{Action, MouseButton};
 
         match *event {
             CursorPos(x, y) => {
-                let pos = Pos2::new(x as f32, y as f32);
+                // GLFW podaje kursor w jednostkach ekranu, egui chce punkty
+                let (pixels_per_point, framebuffer_scale) = window_scale(window);
+                let scale = framebuffer_scale / pixels_per_point;
+                let pos = Pos2::new(x as f32 * scale, y as f32 * scale);
                 self.pointer_pos = Some(pos);
                 self.events.push(Event::PointerMoved(pos));
             }
@@ -55,39 +61,53 @@ impl Gui {
                         _ => return,
                     };
 
-                    let pressed = action == Action::Press;
-
-                    let modifiers = egui::Modifiers {
-                        alt: mods.contains(Modifiers::Alt),
-                        ctrl: mods.contains(Modifiers::Control),
-                        shift: mods.contains(Modifiers::Shift),
-                        mac_cmd: false,
-                        command: mods.contains(Modifiers::Control),
-                    };
-
                     self.events.push(Event::PointerButton {
                         pos,
                         button,
-                        pressed,
-                        modifiers,
+                        pressed: action == Action::Press,
+                        modifiers: egui_modifiers(mods),
                     });
                 }
             }
             Scroll(x, y) => {
-                let modifiers = egui::Modifiers::default(); // lub z `mods`, jeśli przekazujesz
+                // GLFW nie podaje modyfikatorów przy przewijaniu
                 self.events.push(Event::MouseWheel {
                     unit: egui::MouseWheelUnit::Line,
                     delta: vec2(x as f32, y as f32),
-                    modifiers,
+                    modifiers: current_modifiers(window),
                 });
             }
             Char(c) => {
                 self.events.push(Event::Text(c.to_string()));
             }
             Key(key, _scancode, action, mods) => {
-                // Tu można zmapować na Event::Key, jeśli chcesz,
-                // ale minimum do klikania myszą nie jest wymagane.
-                // Warto dodać, jeśli chcesz obsługiwać shortcuty egui.
+                let Some(key) = egui_key(key) else {
+                    return;
+                };
+                let pressed = action != Action::Release;
+                let modifiers = egui_modifiers(mods);
+
+                // skróty schowka egui obsługuje jako osobne zdarzenia
+                if pressed && modifiers.command {
+                    let clipboard = match key {
+                        egui::Key::C => Some(Event::Copy),
+                        egui::Key::X => Some(Event::Cut),
+                        egui::Key::V => window.get_clipboard_string().map(Event::Paste),
+                        _ => None,
+                    };
+                    if let Some(event) = clipboard {
+                        self.events.push(event);
+                        return;
+                    }
+                }
+
+                self.events.push(Event::Key {
+                    key,
+                    physical_key: None,
+                    pressed,
+                    repeat: action == Action::Repeat,
+                    modifiers,
+                });
             }
             _ => {}
         }
@@ -97,36 +117,42 @@ impl Gui {
     where
         F: FnMut(&EguiContext),
     {
-        let (width, height) = window.get_size();
-        let width = width.max(1) as f32;
-        let height = height.max(1) as f32;
+        let (width, height) = window.get_framebuffer_size();
+        let (pixels_per_point, _) = window_scale(window);
+        let width = width.max(1) as f32 / pixels_per_point;
+        let height = height.max(1) as f32 / pixels_per_point;
 
-        let raw_input = egui::RawInput {
+        let mut raw_input = egui::RawInput {
             screen_rect: Some(Rect::from_min_size(
                 Pos2::new(0.0, 0.0),
                 vec2(width, height),
             )),
             time: Some(time),
             events: std::mem::take(&mut self.events),
+            modifiers: current_modifiers(window),
             ..Default::default()
         };
+        if let Some(viewport) = raw_input.viewports.get_mut(&raw_input.viewport_id) {
+            viewport.native_pixels_per_point = Some(pixels_per_point);
+        }
 
         self.ctx.run(raw_input, build_ui)
     }
 
-    pub fn paint(&mut self, window: &glfw::Window, full_output: egui::FullOutput) {
-        let (width, height) = window.get_size();
+    pub fn paint(&mut self, window: &mut glfw::Window, full_output: egui::FullOutput) {
+        let (width, height) = window.get_framebuffer_size();
         let width = width.max(1) as f32;
         let height = height.max(1) as f32;
 
         let egui::FullOutput {
-            platform_output: _,
+            platform_output,
             textures_delta,
             shapes,
+            pixels_per_point,
             ..
         } = full_output;
+        self.handle_platform_output(window, platform_output);
 
-        let pixels_per_point = self.ctx.pixels_per_point();
         let clipped_primitives = self.ctx.tessellate(shapes, pixels_per_point);
 
         // tekstury
@@ -144,4 +170,175 @@ impl Gui {
             &clipped_primitives,
         );
     }
+
+    /// Schowek i kształt kursora zażądane przez egui
+    fn handle_platform_output(&mut self, window: &mut glfw::Window, output: egui::PlatformOutput) {
+        for command in output.commands {
+            if let egui::OutputCommand::CopyText(text) = command {
+                window.set_clipboard_string(&text);
+            }
+        }
+
+        if output.cursor_icon != self.cursor_icon {
+            self.cursor_icon = output.cursor_icon;
+            window.set_cursor(glfw_cursor(output.cursor_icon).map(glfw::Cursor::standard));
+        }
+    }
+}
+
+/// Na macOS skróty idą z Cmd, gdzie indziej z Ctrl
+fn egui_modifiers(mods: glfw::Modifiers) -> egui::Modifiers {
+    use glfw::Modifiers;
+
+    let ctrl = mods.contains(Modifiers::Control);
+    let mac_cmd = cfg!(target_os = "macos") && mods.contains(Modifiers::Super);
+    egui::Modifiers {
+        alt: mods.contains(Modifiers::Alt),
+        ctrl,
+        shift: mods.contains(Modifiers::Shift),
+        mac_cmd,
+        command: if cfg!(target_os = "macos") {
+            mac_cmd
+        } else {
+            ctrl
+        },
+    }
+}
+
+/// Modyfikatory z aktualnego stanu klawiszy – dla zdarzeń, które ich nie niosą
+fn current_modifiers(window: &glfw::Window) -> egui::Modifiers {
+    use glfw::{Action, Key, Modifiers};
+
+    let mut mods = Modifiers::empty();
+    for (left, right, modifier) in [
+        (Key::LeftShift, Key::RightShift, Modifiers::Shift),
+        (Key::LeftControl, Key::RightControl, Modifiers::Control),
+        (Key::LeftAlt, Key::RightAlt, Modifiers::Alt),
+        (Key::LeftSuper, Key::RightSuper, Modifiers::Super),
+    ] {
+        if window.get_key(left) == Action::Press || window.get_key(right) == Action::Press {
+            mods |= modifier;
+        }
+    }
+    egui_modifiers(mods)
+}
+
+fn egui_key(key: glfw::Key) -> Option<egui::Key> {
+    use egui::Key as E;
+    use glfw::Key as G;
+
+    Some(match key {
+        G::Left => E::ArrowLeft,
+        G::Right => E::ArrowRight,
+        G::Up => E::ArrowUp,
+        G::Down => E::ArrowDown,
+        G::Escape => E::Escape,
+        G::Tab => E::Tab,
+        G::Backspace => E::Backspace,
+        G::Enter | G::KpEnter => E::Enter,
+        G::Space => E::Space,
+        G::Insert => E::Insert,
+        G::Delete => E::Delete,
+        G::Home => E::Home,
+        G::End => E::End,
+        G::PageUp => E::PageUp,
+        G::PageDown => E::PageDown,
+        G::Minus | G::KpSubtract => E::Minus,
+        G::Equal | G::KpEqual => E::Equals,
+        G::KpAdd => E::Plus,
+        G::Comma => E::Comma,
+        G::Period | G::KpDecimal => E::Period,
+        G::Slash | G::KpDivide => E::Slash,
+        G::Backslash => E::Backslash,
+        G::Semicolon => E::Semicolon,
+        G::Apostrophe => E::Quote,
+        G::GraveAccent => E::Backtick,
+        G::LeftBracket => E::OpenBracket,
+        G::RightBracket => E::CloseBracket,
+        G::Num0 | G::Kp0 => E::Num0,
+        G::Num1 | G::Kp1 => E::Num1,
+        G::Num2 | G::Kp2 => E::Num2,
+        G::Num3 | G::Kp3 => E::Num3,
+        G::Num4 | G::Kp4 => E::Num4,
+        G::Num5 | G::Kp5 => E::Num5,
+        G::Num6 | G::Kp6 => E::Num6,
+        G::Num7 | G::Kp7 => E::Num7,
+        G::Num8 | G::Kp8 => E::Num8,
+        G::Num9 | G::Kp9 => E::Num9,
+        G::A => E::A,
+        G::B => E::B,
+        G::C => E::C,
+        G::D => E::D,
+        G::E => E::E,
+        G::F => E::F,
+        G::G => E::G,
+        G::H => E::H,
+        G::I => E::I,
+        G::J => E::J,
+        G::K => E::K,
+        G::L => E::L,
+        G::M => E::M,
+        G::N => E::N,
+        G::O => E::O,
+        G::P => E::P,
+        G::Q => E::Q,
+        G::R => E::R,
+        G::S => E::S,
+        G::T => E::T,
+        G::U => E::U,
+        G::V => E::V,
+        G::W => E::W,
+        G::X => E::X,
+        G::Y => E::Y,
+        G::Z => E::Z,
+        G::F1 => E::F1,
+        G::F2 => E::F2,
+        G::F3 => E::F3,
+        G::F4 => E::F4,
+        G::F5 => E::F5,
+        G::F6 => E::F6,
+        G::F7 => E::F7,
+        G::F8 => E::F8,
+        G::F9 => E::F9,
+        G::F10 => E::F10,
+        G::F11 => E::F11,
+        G::F12 => E::F12,
+        _ => return None,
+    })
+}
+
+/// Najbliższy kursor standardowy GLFW; `None` – domyślna strzałka
+fn glfw_cursor(icon: egui::CursorIcon) -> Option<glfw::StandardCursor> {
+    use egui::CursorIcon as C;
+    use glfw::StandardCursor as S;
+
+    match icon {
+        C::Text | C::VerticalText => Some(S::IBeam),
+        C::PointingHand => Some(S::Hand),
+        C::Crosshair | C::Move | C::AllScroll => Some(S::Crosshair),
+        C::ResizeHorizontal | C::ResizeEast | C::ResizeWest | C::ResizeColumn => Some(S::HResize),
+        C::ResizeVertical | C::ResizeNorth | C::ResizeSouth | C::ResizeRow => Some(S::VResize),
+        _ => None,
+    }
+}
+
+/// Skala HiDPI okna: (pixels_per_point dla egui, piksele framebuffera na jednostkę ekranu).
+/// Na macOS Retina oba wynoszą 2, na Windows/Linux ze skalowaniem 150%: (1.5, 1).
+fn window_scale(window: &glfw::Window) -> (f32, f32) {
+    let (window_width, _) = window.get_size();
+    let (framebuffer_width, _) = window.get_framebuffer_size();
+    let framebuffer_scale = if window_width > 0 && framebuffer_width > 0 {
+        framebuffer_width as f32 / window_width as f32
+    } else {
+        1.0
+    };
+
+    let (content_scale, _) = window.get_content_scale();
+    let pixels_per_point = if content_scale > 0.0 {
+        content_scale
+    } else {
+        framebuffer_scale
+    };
+
+    (pixels_per_point, framebuffer_scale)
 }
@@ -1,8 +1,51 @@
 use glfw::{Action, Key};
 
-use crate::camera::Camera;
+use crate::camera::{Camera, CameraMode, MAX_PITCH, MAX_RADIUS, MIN_RADIUS, THETA_MARGIN};
 
-pub fn process_input(window: &mut glfw::Window, dt: f32, camera: &mut Camera) {
+/// Stan wejścia potrzebny między klatkami
+#[derive(Default)]
+pub struct InputState {
+    /// Ostatnia pozycja kursora (do liczenia ruchu myszy w trybie lotu)
+    last_cursor: Option<(f64, f64)>,
+}
+
+/// Wejście zajęte przez egui (pisanie w polu tekstowym, przeciąganie
+/// suwaka) – kamera go wtedy nie dostaje
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GuiFocus {
+    pub keyboard: bool,
+    pub pointer: bool,
+}
+
+pub fn process_input(
+    window: &mut glfw::Window,
+    dt: f32,
+    camera: &mut Camera,
+    state: &mut InputState,
+    focus: GuiFocus,
+) {
+    match camera.mode {
+        CameraMode::Orbit => {
+            state.last_cursor = None;
+            if !focus.keyboard {
+                process_orbit(window, dt, camera);
+            }
+        }
+        CameraMode::FreeFly => process_free_fly(window, dt, camera, state, focus),
+    }
+}
+
+/// Przełącza tryb kamery i przechwytuje kursor w trybie lotu
+pub fn toggle_camera_mode(window: &mut glfw::Window, camera: &mut Camera) {
+    camera.toggle_mode();
+    let cursor = match camera.mode {
+        CameraMode::Orbit => glfw::CursorMode::Normal,
+        CameraMode::FreeFly => glfw::CursorMode::Disabled,
+    };
+    window.set_cursor_mode(cursor);
+}
+
+fn process_orbit(window: &mut glfw::Window, dt: f32, camera: &mut Camera) {
     let rot_speed = 1.5;
     let zoom_speed = 10.0;
 
@@ -31,18 +74,70 @@ pub fn process_input(window: &mut glfw::Window, dt: f32, camera: &mut Camera) {
     }
 
     // ograniczenia kamery
-    if camera.radius < 3.0 {
-        camera.radius = 3.0;
+    camera.radius = camera.radius.clamp(MIN_RADIUS, MAX_RADIUS);
+    camera.theta = camera
+        .theta
+        .clamp(THETA_MARGIN, std::f32::consts::PI - THETA_MARGIN);
+}
+
+fn process_free_fly(
+    window: &mut glfw::Window,
+    dt: f32,
+    camera: &mut Camera,
+    state: &mut InputState,
+    focus: GuiFocus,
+) {
+    let move_speed = 5.0;
+    let mouse_sensitivity = 0.002;
+
+    // rozglądanie myszą; po oddaniu kursora przez egui liczymy od nowa, bez skoku
+    if focus.pointer {
+        state.last_cursor = None;
+    } else {
+        let cursor = window.get_cursor_pos();
+        if let Some((last_x, last_y)) = state.last_cursor {
+            camera.yaw += (cursor.0 - last_x) as f32 * mouse_sensitivity;
+            camera.pitch -= (cursor.1 - last_y) as f32 * mouse_sensitivity;
+            camera.pitch = camera.pitch.clamp(-MAX_PITCH, MAX_PITCH);
+        }
+        state.last_cursor = Some(cursor);
     }
-    if camera.radius > 50.0 {
-        camera.radius = 50.0;
+
+    if focus.keyboard {
+        return;
     }
 
-    let eps = 0.1;
-    if camera.theta < eps {
-        camera.theta = eps;
+    // Shift – szybciej, Ctrl – wolniej
+    let mut speed = move_speed;
+    if window.get_key(Key::LeftShift) == Action::Press {
+        speed *= 4.0;
+    }
+    if window.get_key(Key::LeftControl) == Action::Press {
+        speed *= 0.25;
+    }
+
+    let forward = camera.forward();
+    let right = camera.right();
+    let up = cgmath::Vector3::new(0.0, 1.0, 0.0);
+    let step = speed * dt;
+
+    // WASD – ruch w płaszczyźnie widoku, E/Q – góra/dół
+    if window.get_key(Key::W) == Action::Press {
+        camera.position += forward * step;
+    }
+    if window.get_key(Key::S) == Action::Press {
+        camera.position -= forward * step;
+    }
+    if window.get_key(Key::D) == Action::Press {
+        camera.position += right * step;
+    }
+    if window.get_key(Key::A) == Action::Press {
+        camera.position -= right * step;
+    }
+    if window.get_key(Key::E) == Action::Press {
+        camera.position += up * step;
     }
-    if camera.theta > std::f32::consts::PI - eps {
-        camera.theta = std::f32::consts::PI - eps;
+    if window.get_key(Key::Q) == Action::Press {
+        camera.position -= up * step;
     }
 }
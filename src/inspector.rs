@@ -0,0 +1,289 @@
+use std::rc::Rc;
+
+use cgmath::{Deg, Euler, Quaternion, Vector3};
+
+use crate::animation::Animator;
+use crate::components::{ColorAnimation, Name, Renderable, RotationAnimation};
+use crate::ecs::{Entity, World};
+use crate::material::{DIFFUSE_SLOT, Material, MaterialParam, RenderMode};
+use crate::scene_graph::{self, Parent, Transform};
+use crate::textures::Texture;
+
+/// Okna "Objects" (lista encji) i "Inspector" (edycja zaznaczonej)
+#[derive(Default)]
+pub struct Inspector {
+    /// Zaznaczona encja – wybrana z listy albo kliknięciem w scenie
+    pub selected: Option<Entity>,
+    filter: String,
+    texture_path: String,
+}
+
+impl Inspector {
+    pub fn new() -> Self {
+        Inspector::default()
+    }
+
+    /// Rysuje oba okna. `textures` to wczytane tekstury (ścieżka, uchwyt).
+    /// Zwraca encję i ścieżkę tekstury do wczytania – tym zajmuje się silnik.
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        world: &mut World,
+        textures: &[(String, Rc<Texture>)],
+    ) -> Option<(Entity, String)> {
+        egui::Window::new("Objects").show(ctx, |ui| self.objects_ui(ui, world));
+
+        let mut load = None;
+        egui::Window::new("Inspector").show(ctx, |ui| match self.selected {
+            Some(entity) if world.contains(entity) => {
+                load = self.entity_ui(ui, world, entity, textures);
+            }
+            _ => {
+                ui.label("Nothing selected");
+            }
+        });
+        load
+    }
+
+    fn objects_ui(&mut self, ui: &mut egui::Ui, world: &World) {
+        ui.horizontal(|ui| {
+            ui.label("Filter:");
+            ui.text_edit_singleline(&mut self.filter);
+        });
+
+        let filter = self.filter.to_lowercase();
+        let objects: Vec<(Entity, String)> = world
+            .query::<&Transform>()
+            .map(|(entity, _)| (entity, entity_name(world, entity)))
+            .filter(|(_, name)| name.to_lowercase().contains(&filter))
+            .collect();
+        ui.label(format!("{} objects", objects.len()));
+        ui.separator();
+
+        // scatter potrafi dodać dziesiątki tysięcy encji – rysujemy tylko widoczne wiersze
+        let row_height = ui.text_style_height(&egui::TextStyle::Button);
+        egui::ScrollArea::vertical().max_height(300.0).show_rows(
+            ui,
+            row_height,
+            objects.len(),
+            |ui, rows| {
+                for (entity, name) in &objects[rows] {
+                    let label = format!("{} ({})", name, entity.index());
+                    let selected = self.selected == Some(*entity);
+                    if ui.selectable_label(selected, label).clicked() {
+                        self.selected = Some(*entity);
+                    }
+                }
+            },
+        );
+    }
+
+    fn entity_ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        world: &mut World,
+        entity: Entity,
+        textures: &[(String, Rc<Texture>)],
+    ) -> Option<(Entity, String)> {
+        ui.heading(entity_name(world, entity));
+        ui.label(format!("{:?}", entity));
+        if let Some(Parent(parent)) = world.get::<Parent>(entity).copied() {
+            ui.horizontal(|ui| {
+                ui.label("Parent:");
+                if ui.link(entity_name(world, parent)).clicked() {
+                    self.selected = Some(parent);
+                }
+                if ui.button("Detach").clicked() {
+                    scene_graph::detach(world, entity);
+                }
+            });
+        }
+        let delete = ui
+            .horizontal(|ui| {
+                if ui.button("Deselect").clicked() {
+                    self.selected = None;
+                }
+                ui.button("Delete").clicked()
+            })
+            .inner;
+        if delete {
+            scene_graph::despawn(world, entity);
+            self.selected = None;
+            return None;
+        }
+
+        if let Some(mut transform) = world.get::<Transform>(entity).copied() {
+            ui.collapsing("Transform", |ui| {
+                if transform_ui(ui, &mut transform) {
+                    world.insert(entity, transform);
+                }
+            });
+        }
+
+        let mut load = None;
+        if let Some(mut renderable) = world.get::<Renderable>(entity).cloned() {
+            ui.collapsing("Appearance", |ui| {
+                let mut changed = false;
+                ui.horizontal(|ui| {
+                    ui.label("Tint");
+                    let mut tint = renderable.tint.into();
+                    if ui.color_edit_button_rgb(&mut tint).changed() {
+                        renderable.tint = tint.into();
+                        changed = true;
+                    }
+                });
+
+                // zmiana materiału współdzielonego z innymi obiektami
+                // daje tej encji własną kopię
+                let mut material = (*renderable.material).clone();
+                if material_ui(ui, &mut material) {
+                    renderable.material = Rc::new(material);
+                    changed = true;
+                }
+
+                ui.separator();
+                if let Some(texture) = texture_ui(ui, &renderable.material, textures) {
+                    let mut material = (*renderable.material).clone();
+                    material.textures.insert(DIFFUSE_SLOT.to_string(), texture);
+                    renderable.material = Rc::new(material);
+                    changed = true;
+                }
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(&mut self.texture_path);
+                    if ui.button("Load texture").clicked() && !self.texture_path.is_empty() {
+                        load = Some((entity, self.texture_path.clone()));
+                    }
+                });
+
+                if changed {
+                    world.insert(entity, renderable);
+                }
+            });
+        }
+
+        ui.collapsing("Animation", |ui| animation_ui(ui, world, entity));
+        load
+    }
+}
+
+/// Nazwa z komponentu `Name` albo identyfikator encji
+pub fn entity_name(world: &World, entity: Entity) -> String {
+    world
+        .get::<Name>(entity)
+        .map_or_else(|| format!("{:?}", entity), |name| name.0.clone())
+}
+
+fn transform_ui(ui: &mut egui::Ui, transform: &mut Transform) -> bool {
+    let mut changed = vector_ui(ui, "Position", &mut transform.translation, 0.05);
+
+    let euler = Euler::from(transform.rotation);
+    let mut degrees = Vector3::new(
+        Deg::from(euler.x).0,
+        Deg::from(euler.y).0,
+        Deg::from(euler.z).0,
+    );
+    if vector_ui(ui, "Rotation", &mut degrees, 1.0) {
+        transform.rotation =
+            Quaternion::from(Euler::new(Deg(degrees.x), Deg(degrees.y), Deg(degrees.z)));
+        changed = true;
+    }
+
+    changed |= vector_ui(ui, "Scale", &mut transform.scale, 0.01);
+    changed
+}
+
+fn vector_ui(ui: &mut egui::Ui, label: &str, value: &mut Vector3<f32>, speed: f32) -> bool {
+    ui.horizontal(|ui| {
+        ui.label(label);
+        let mut changed = false;
+        for component in [&mut value.x, &mut value.y, &mut value.z] {
+            changed |= ui
+                .add(egui::DragValue::new(component).speed(speed))
+                .changed();
+        }
+        changed
+    })
+    .inner
+}
+
+fn material_ui(ui: &mut egui::Ui, material: &mut Material) -> bool {
+    let mut changed = false;
+    ui.label(format!("Material: {}", material.name));
+
+    egui::ComboBox::from_label("Render mode")
+        .selected_text(format!("{:?}", material.flags.mode))
+        .show_ui(ui, |ui| {
+            for mode in [RenderMode::Opaque, RenderMode::Cutout, RenderMode::Blended] {
+                changed |= ui
+                    .selectable_value(&mut material.flags.mode, mode, format!("{:?}", mode))
+                    .changed();
+            }
+        });
+    changed |= ui
+        .checkbox(&mut material.flags.cast_shadows, "Cast shadows")
+        .changed();
+
+    for (name, param) in material.params.iter_mut() {
+        ui.horizontal(|ui| {
+            ui.label(name.as_str());
+            changed |= match param {
+                MaterialParam::Float(value) => {
+                    ui.add(egui::DragValue::new(value).speed(0.01)).changed()
+                }
+                MaterialParam::Int(value) => ui.add(egui::DragValue::new(value)).changed(),
+                MaterialParam::Vec3(value) => {
+                    let mut rgb = (*value).into();
+                    let response = ui.color_edit_button_rgb(&mut rgb);
+                    *value = rgb.into();
+                    response.changed()
+                }
+            };
+        });
+    }
+    changed
+}
+
+/// Wybór tekstury koloru spośród wczytanych
+fn texture_ui(
+    ui: &mut egui::Ui,
+    material: &Material,
+    textures: &[(String, Rc<Texture>)],
+) -> Option<Rc<Texture>> {
+    let current = material.texture(DIFFUSE_SLOT)?;
+    let current_path = textures
+        .iter()
+        .find(|(_, texture)| Rc::ptr_eq(texture, current))
+        .map_or("<unknown>", |(path, _)| path.as_str());
+
+    let mut selected = None;
+    egui::ComboBox::from_label("Texture")
+        .selected_text(current_path)
+        .show_ui(ui, |ui| {
+            for (path, texture) in textures {
+                let checked = Rc::ptr_eq(texture, current);
+                if ui.selectable_label(checked, path).clicked() && !checked {
+                    selected = Some(texture.clone());
+                }
+            }
+        });
+    selected
+}
+
+fn animation_ui(ui: &mut egui::Ui, world: &mut World, entity: Entity) {
+    let mut any = false;
+    if let Some(animation) = world.get_mut::<RotationAnimation>(entity) {
+        ui.add(egui::Slider::new(&mut animation.speed, -5.0..=5.0).text("Rotation speed"));
+        any = true;
+    }
+    if let Some(animation) = world.get_mut::<ColorAnimation>(entity) {
+        ui.add(egui::Slider::new(&mut animation.speed, 0.0..=10.0).text("Color speed"));
+        any = true;
+    }
+    if let Some(animator) = world.get_mut::<Animator>(entity) {
+        ui.add(egui::Slider::new(&mut animator.speed, 0.0..=3.0).text("Clip speed"));
+        any = true;
+    }
+    if !any {
+        ui.label("No animations");
+    }
+}
@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+use std::mem;
+use std::ptr;
+use std::rc::Rc;
+
+use cgmath::{Matrix4, Vector3};
+
+use crate::components::{ColorAnimation, Renderable};
+use crate::material::Material;
+use crate::mesh::Mesh;
+
+/// Pierwszy atrybut egzemplarza: a_model zajmuje lokalizacje 3..=6, a_tint 7
+const INSTANCE_ATTRIB: u32 = 3;
+
+/// Dane jednego egzemplarza w buforze (układ atrybutów a_model + a_tint)
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct InstanceData {
+    pub model: [[f32; 4]; 4],
+    pub tint: [f32; 3],
+}
+
+impl InstanceData {
+    pub fn new(model: Matrix4<f32>, tint: Vector3<f32>) -> Self {
+        InstanceData {
+            model: model.into(),
+            tint: tint.into(),
+        }
+    }
+}
+
+/// Obiekty o wspólnej siatce i materiale rysowane jednym wywołaniem
+pub struct Batch<'a> {
+    pub mesh: &'a Rc<Mesh>,
+    pub material: &'a Rc<Material>,
+    pub instances: Vec<InstanceData>,
+    /// Animacja koloru jedynej encji partii – z własnymi uniformami encja trafia do partii sama
+    pub single: Option<&'a ColorAnimation>,
+}
+
+impl<'a> Batch<'a> {
+    pub fn new(item: &DrawItem<'a>) -> Self {
+        Batch {
+            mesh: &item.renderable.mesh,
+            material: &item.renderable.material,
+            instances: Vec::new(),
+            single: None,
+        }
+    }
+}
+
+/// Encja do narysowania z policzonymi danymi egzemplarza
+pub struct DrawItem<'a> {
+    pub renderable: &'a Renderable,
+    pub color_animation: Option<&'a ColorAnimation>,
+    pub instance: InstanceData,
+}
+
+/// Grupuje obiekty po (siatka, materiał) w kolejności pierwszego wystąpienia;
+/// egzemplarze w partii zachowują kolejność wejścia.
+/// `per_object_uniforms` = false przy przebiegu cieni, gdzie kolor nie ma znaczenia.
+pub fn build_batches<'a, I>(items: I, per_object_uniforms: bool) -> Vec<Batch<'a>>
+where
+    I: IntoIterator<Item = DrawItem<'a>>,
+{
+    let mut batches: Vec<Batch> = Vec::new();
+    let mut index: HashMap<(*const Mesh, *const Material), usize> = HashMap::new();
+
+    for item in items {
+        if let Some(animation) = item.color_animation.filter(|_| per_object_uniforms) {
+            let mut batch = Batch::new(&item);
+            batch.instances.push(item.instance);
+            batch.single = Some(animation);
+            batches.push(batch);
+            continue;
+        }
+
+        let renderable = item.renderable;
+        let key = (
+            Rc::as_ptr(&renderable.mesh),
+            Rc::as_ptr(&renderable.material),
+        );
+        let slot = *index.entry(key).or_insert_with(|| {
+            batches.push(Batch::new(&item));
+            batches.len() - 1
+        });
+        batches[slot].instances.push(item.instance);
+    }
+
+    batches
+}
+
+/// Jak `build_batches`, ale łączy tylko sąsiednie obiekty – kolejność
+/// rysowania zostaje dokładnie taka jak na wejściu (obiekty przezroczyste)
+pub fn build_ordered_batches<'a, I>(items: I) -> Vec<Batch<'a>>
+where
+    I: IntoIterator<Item = DrawItem<'a>>,
+{
+    let mut batches: Vec<Batch> = Vec::new();
+
+    for item in items {
+        let renderable = item.renderable;
+        let joins_last = batches.last().is_some_and(|last: &Batch| {
+            last.single.is_none()
+                && item.color_animation.is_none()
+                && Rc::ptr_eq(last.mesh, &renderable.mesh)
+                && Rc::ptr_eq(last.material, &renderable.material)
+        });
+
+        if !joins_last {
+            let mut batch = Batch::new(&item);
+            batch.single = item.color_animation;
+            batches.push(batch);
+        }
+        if let Some(last) = batches.last_mut() {
+            last.instances.push(item.instance);
+        }
+    }
+
+    batches
+}
+
+/// Bufor VBO z danymi egzemplarzy, podpinany do VAO rysowanej siatki
+pub struct InstanceBuffer {
+    vbo: u32,
+}
+
+impl InstanceBuffer {
+    pub fn new() -> Self {
+        let mut vbo = 0;
+        unsafe {
+            gl::GenBuffers(1, &mut vbo);
+        }
+        InstanceBuffer { vbo }
+    }
+
+    /// Wysyła egzemplarze i rysuje je jednym glDrawElementsInstanced
+    pub fn draw(&self, mesh: &Mesh, instances: &[InstanceData]) {
+        if instances.is_empty() {
+            return;
+        }
+
+        let stride = mem::size_of::<InstanceData>() as i32;
+        unsafe {
+            gl::BindVertexArray(mesh.vao);
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo);
+            // nowy bufor co partię – sterownik nie czeka na poprzednie rysowanie
+            gl::BufferData(
+                gl::ARRAY_BUFFER,
+                mem::size_of_val(instances) as isize,
+                instances.as_ptr() as *const _,
+                gl::STREAM_DRAW,
+            );
+
+            // mat4 to cztery kolejne atrybuty vec4
+            for column in 0..4 {
+                let location = INSTANCE_ATTRIB + column;
+                gl::EnableVertexAttribArray(location);
+                gl::VertexAttribPointer(
+                    location,
+                    4,
+                    gl::FLOAT,
+                    gl::FALSE,
+                    stride,
+                    (column as usize * 4 * mem::size_of::<f32>()) as *const _,
+                );
+                gl::VertexAttribDivisor(location, 1);
+            }
+
+            let tint = INSTANCE_ATTRIB + 4;
+            gl::EnableVertexAttribArray(tint);
+            gl::VertexAttribPointer(
+                tint,
+                3,
+                gl::FLOAT,
+                gl::FALSE,
+                stride,
+                mem::offset_of!(InstanceData, tint) as *const _,
+            );
+            gl::VertexAttribDivisor(tint, 1);
+
+            gl::DrawElementsInstanced(
+                gl::TRIANGLES,
+                mesh.index_count,
+                gl::UNSIGNED_INT,
+                ptr::null(),
+                instances.len() as i32,
+            );
+
+            gl::BindVertexArray(0);
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+        }
+    }
+}
+
+impl Default for InstanceBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for InstanceBuffer {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteBuffers(1, &self.vbo);
+        }
+    }
+}
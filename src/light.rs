@@ -0,0 +1,205 @@
+use std::mem;
+use std::ptr;
+
+use cgmath::{InnerSpace, Point3, Vector3};
+
+/// Maksymalna liczba świateł – musi się zgadzać z MAX_LIGHTS w basic.frag
+pub const MAX_LIGHTS: usize = 32;
+
+/// Punkt wiązania bloku `Lights` (layout std140)
+pub const LIGHTS_BINDING: u32 = 0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LightKind {
+    Directional,
+    Point,
+    Spot,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Light {
+    pub kind: LightKind,
+    /// Pozycja (point, spot)
+    pub position: Point3<f32>,
+    /// Kierunek świecenia (directional, spot)
+    pub direction: Vector3<f32>,
+    pub color: Vector3<f32>,
+    pub intensity: f32,
+    /// Odległość, na której światło gaśnie do zera (point, spot)
+    pub range: f32,
+    /// Stożek reflektora w stopniach: pełne światło do `inner_angle`,
+    /// zanik do zera przy `outer_angle`
+    pub inner_angle: f32,
+    pub outer_angle: f32,
+}
+
+impl Light {
+    pub fn directional(direction: Vector3<f32>, color: Vector3<f32>, intensity: f32) -> Self {
+        Light {
+            kind: LightKind::Directional,
+            position: Point3::new(0.0, 0.0, 0.0),
+            direction: direction.normalize(),
+            color,
+            intensity,
+            range: 0.0,
+            inner_angle: 0.0,
+            outer_angle: 0.0,
+        }
+    }
+
+    pub fn point(position: Point3<f32>, color: Vector3<f32>, intensity: f32, range: f32) -> Self {
+        Light {
+            kind: LightKind::Point,
+            position,
+            direction: Vector3::new(0.0, -1.0, 0.0),
+            color,
+            intensity,
+            range,
+            inner_angle: 0.0,
+            outer_angle: 0.0,
+        }
+    }
+
+    /// Reflektor ze stożkiem 20°/30° (zmiana przez `with_cone`)
+    pub fn spot(
+        position: Point3<f32>,
+        direction: Vector3<f32>,
+        color: Vector3<f32>,
+        intensity: f32,
+        range: f32,
+    ) -> Self {
+        Light {
+            kind: LightKind::Spot,
+            position,
+            direction: direction.normalize(),
+            color,
+            intensity,
+            range,
+            inner_angle: 20.0,
+            outer_angle: 30.0,
+        }
+    }
+
+    pub fn with_cone(mut self, inner_angle: f32, outer_angle: f32) -> Self {
+        self.inner_angle = inner_angle;
+        self.outer_angle = outer_angle.max(inner_angle);
+        self
+    }
+
+    /// Światło, które wcześniej było zaszyte w basic.frag
+    pub fn sun() -> Self {
+        Light::directional(
+            -Vector3::new(0.3, 1.0, 0.5),
+            Vector3::new(1.0, 1.0, 1.0),
+            0.7,
+        )
+    }
+
+    fn to_gpu(self) -> GpuLight {
+        let kind = match self.kind {
+            LightKind::Directional => 0.0,
+            LightKind::Point => 1.0,
+            LightKind::Spot => 2.0,
+        };
+        let p = self.position;
+        let d = self.direction;
+        let c = self.color;
+
+        GpuLight {
+            position: [p.x, p.y, p.z, kind],
+            direction: [d.x, d.y, d.z, self.range.max(0.001)],
+            color: [c.x, c.y, c.z, self.intensity],
+            cone: [
+                self.inner_angle.to_radians().cos(),
+                self.outer_angle.to_radians().cos(),
+                0.0,
+                0.0,
+            ],
+        }
+    }
+}
+
+// Układ pamięci odpowiada blokowi `Lights` w std140:
+// vec4 u_ambient; int u_light_count; Light u_lights[MAX_LIGHTS];
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct GpuLight {
+    /// xyz pozycja, w rodzaj światła
+    position: [f32; 4],
+    /// xyz kierunek, w zasięg
+    direction: [f32; 4],
+    /// rgb kolor, w natężenie
+    color: [f32; 4],
+    /// x cos(inner), y cos(outer)
+    cone: [f32; 4],
+}
+
+#[repr(C)]
+struct LightBlock {
+    ambient: [f32; 4],
+    count: i32,
+    _pad: [i32; 3],
+    lights: [GpuLight; MAX_LIGHTS],
+}
+
+/// Uniform buffer ze światłami, wysyłany co klatkę
+pub struct LightBuffer {
+    ubo: u32,
+}
+
+impl LightBuffer {
+    pub fn new() -> Self {
+        let mut ubo = 0;
+        unsafe {
+            gl::GenBuffers(1, &mut ubo);
+            gl::BindBuffer(gl::UNIFORM_BUFFER, ubo);
+            gl::BufferData(
+                gl::UNIFORM_BUFFER,
+                mem::size_of::<LightBlock>() as isize,
+                ptr::null(),
+                gl::DYNAMIC_DRAW,
+            );
+            gl::BindBuffer(gl::UNIFORM_BUFFER, 0);
+            gl::BindBufferBase(gl::UNIFORM_BUFFER, LIGHTS_BINDING, ubo);
+        }
+        LightBuffer { ubo }
+    }
+
+    /// Wysyła światła do GPU; nadmiarowe (ponad MAX_LIGHTS) są pomijane
+    pub fn upload(&self, lights: &[Light], ambient: Vector3<f32>) {
+        let mut block = LightBlock {
+            ambient: [ambient.x, ambient.y, ambient.z, 0.0],
+            count: lights.len().min(MAX_LIGHTS) as i32,
+            _pad: [0; 3],
+            lights: [GpuLight::default(); MAX_LIGHTS],
+        };
+        for (slot, light) in block.lights.iter_mut().zip(lights) {
+            *slot = light.to_gpu();
+        }
+
+        unsafe {
+            gl::BindBuffer(gl::UNIFORM_BUFFER, self.ubo);
+            gl::BufferSubData(
+                gl::UNIFORM_BUFFER,
+                0,
+                mem::size_of::<LightBlock>() as isize,
+                &block as *const LightBlock as *const _,
+            );
+            gl::BindBuffer(gl::UNIFORM_BUFFER, 0);
+        }
+    }
+}
+
+impl Default for LightBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for LightBuffer {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteBuffers(1, &self.ubo);
+        }
+    }
+}
@@ -1,18 +1,93 @@
 // mod ex; //testing examples
 
+mod animation;
+mod assets;
+mod bounds;
 mod camera;
+mod components;
+mod ecs;
 mod engine;
+mod file_menu;
+mod framebuffer;
+mod gizmo;
 mod glcontext;
 mod gui;
 mod input;
+mod inspector;
+mod instancing;
+mod light;
+mod material;
 mod mesh;
-mod scene_object;
+mod picking;
+mod render_queue;
+mod scene;
+mod scene_graph;
 mod shader;
+mod shadow;
 mod textures;
 
+const USAGE: &str = "\
+Usage: rusty_engine [--scene <file.ron>] [--screenshot <out.png> [--size <W>x<H>]]
+
+  --scene <file.ron>      scene to load (default assets/scenes/default.ron)
+  --screenshot <out.png>  render the scene once to a PNG file and exit
+  --size <W>x<H>          screenshot size (default 1280x720)
+
+--screenshot still needs a display: the context comes from a hidden GLFW
+window, so on Linux it requires an X server. On machines without a display
+or GPU the supported route is a virtual X server with Mesa's software
+renderer (this is what CI runs):
+  LIBGL_ALWAYS_SOFTWARE=1 xvfb-run -a rusty_engine --screenshot out.png";
+
 fn main() {
     //ex::hello_triangle::run(); // ex1
 
-    let mut engine = engine::Engine::new(1280, 720, "Rust OBJ Scene");
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|a| a == "--help" || a == "-h") {
+        println!("{}", USAGE);
+        return;
+    }
+    let scene_path = arg_value(&args, "--scene").unwrap_or(scene::DEFAULT_SCENE);
+
+    // tryb bez okna: rusty_engine --screenshot out.png [--size 1920x1080];
+    // kontekst GL daje ukryte okno GLFW (tylko X11), więc na Linuksie
+    // potrzebny jest serwer X – na maszynach bez ekranu xvfb-run, jak w CI
+    if let Some(path) = arg_value(&args, "--screenshot") {
+        if cfg!(target_os = "linux") && std::env::var_os("DISPLAY").is_none() {
+            usage_error("No display found (DISPLAY is not set).");
+        }
+
+        let (width, height) = match arg_value(&args, "--size") {
+            Some(size) => parse_size(size)
+                .unwrap_or_else(|| usage_error(&format!("Invalid --size {:?}.", size))),
+            None => (1280, 720),
+        };
+
+        let engine = engine::Engine::new_offscreen(width as u32, height as u32, scene_path);
+        if let Err(e) = engine.save_screenshot(path, width, height) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let mut engine = engine::Engine::new(1280, 720, "Rust OBJ Scene", scene_path);
     engine.run();
 }
+
+fn usage_error(message: &str) -> ! {
+    eprintln!("{}\n\n{}", message, USAGE);
+    std::process::exit(1);
+}
+
+fn arg_value<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    let index = args.iter().position(|a| a == name)?;
+    args.get(index + 1).map(|s| s.as_str())
+}
+
+/// "1920x1080" -> (1920, 1080)
+fn parse_size(size: &str) -> Option<(i32, i32)> {
+    let (w, h) = size.split_once('x')?;
+    let (w, h): (i32, i32) = (w.parse().ok()?, h.parse().ok()?);
+    if w > 0 && h > 0 { Some((w, h)) } else { None }
+}
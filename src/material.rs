@@ -0,0 +1,185 @@
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use cgmath::Vector3;
+
+use crate::shader::Program;
+use crate::textures::Texture;
+
+/// Domyślny program materiałów (gradient + tekstura + światła)
+pub const BASIC_VERT: &str = "assets/shaders/basic.vert";
+pub const BASIC_FRAG: &str = "assets/shaders/basic.frag";
+
+/// PBR metallic-roughness; wierzchołki wspólne z basic
+pub const PBR_VERT: &str = BASIC_VERT;
+pub const PBR_FRAG: &str = "assets/shaders/pbr.frag";
+
+/// Slot tekstury koloru z alfą – z niego korzysta też alpha cutout w mapie cieni
+pub const DIFFUSE_SLOT: &str = "u_diffuse";
+
+/// Wartość uniformu ustawiana przez materiał
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MaterialParam {
+    Float(f32),
+    Int(i32),
+    Vec3(Vector3<f32>),
+}
+
+/// Sposób łączenia z tłem – decyduje też o przebiegu w kolejce renderowania
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RenderMode {
+    #[default]
+    Opaque,
+    /// Piksele tekstury `DIFFUSE_SLOT` z alfą < 0.5 są odrzucane (też w mapie cieni)
+    Cutout,
+    /// Mieszanie z alfą (tekstura * `u_opacity`), rysowane od najdalszych, nie rzuca cieni
+    Blended,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderFlags {
+    pub mode: RenderMode,
+    pub cast_shadows: bool,
+}
+
+impl Default for RenderFlags {
+    fn default() -> Self {
+        RenderFlags {
+            mode: RenderMode::Opaque,
+            cast_shadows: true,
+        }
+    }
+}
+
+/// Wygląd obiektu: program, tekstury w nazwanych slotach (nazwa samplera
+/// w shaderze), parametry i flagi. Współdzielony między obiektami przez `Rc`.
+#[derive(Clone)]
+pub struct Material {
+    pub name: String,
+    pub program: Rc<Program>,
+    /// Slot -> tekstura; jednostki tekstur przydzielane są po kolei od 0
+    pub textures: BTreeMap<String, Rc<Texture>>,
+    pub params: BTreeMap<String, MaterialParam>,
+    pub flags: RenderFlags,
+}
+
+impl Material {
+    pub fn new(name: impl Into<String>, program: Rc<Program>) -> Self {
+        Material {
+            name: name.into(),
+            program,
+            textures: BTreeMap::new(),
+            params: BTreeMap::new(),
+            flags: RenderFlags::default(),
+        }
+    }
+
+    /// Materiał dla basic.frag: gradient `color1` -> `color2` po normalnej
+    /// (dół -> góra), pomnożony przez teksturę `u_diffuse`
+    pub fn basic(
+        name: impl Into<String>,
+        program: Rc<Program>,
+        color1: Vector3<f32>,
+        color2: Vector3<f32>,
+        diffuse: Rc<Texture>,
+    ) -> Self {
+        Material::new(name, program)
+            .with_texture(DIFFUSE_SLOT, diffuse)
+            .with_param("u_opacity", MaterialParam::Float(1.0))
+            .with_param("u_color1", MaterialParam::Vec3(color1))
+            .with_param("u_color2", MaterialParam::Vec3(color2))
+            .with_param(
+                "u_gradient_normal",
+                MaterialParam::Vec3(Vector3::new(0.0, 1.0, 0.0)),
+            )
+            .with_param(
+                "u_gradient_world",
+                MaterialParam::Vec3(Vector3::new(0.0, 0.0, 0.0)),
+            )
+    }
+
+    /// Materiał dla pbr.frag z domyślnymi wartościami: albedo 1, metallic 0,
+    /// roughness 0.5, bez emisji. Sloty tekstur są wypełnione neutralnymi
+    /// teksturami (`white`, `flat_normal`) – podmienia się je przez `with_texture`.
+    pub fn pbr(
+        name: impl Into<String>,
+        program: Rc<Program>,
+        white: Rc<Texture>,
+        flat_normal: Rc<Texture>,
+    ) -> Self {
+        Material::new(name, program)
+            .with_texture(DIFFUSE_SLOT, white.clone())
+            .with_texture("u_normal_map", flat_normal)
+            .with_texture("u_metallic_map", white.clone())
+            .with_texture("u_roughness_map", white.clone())
+            .with_texture("u_ao_map", white.clone())
+            .with_texture("u_emissive_map", white)
+            .with_param("u_albedo", MaterialParam::Vec3(Vector3::new(1.0, 1.0, 1.0)))
+            .with_param("u_opacity", MaterialParam::Float(1.0))
+            .with_param("u_metallic", MaterialParam::Float(0.0))
+            .with_param("u_roughness", MaterialParam::Float(0.5))
+            .with_param("u_ao", MaterialParam::Float(1.0))
+            .with_param(
+                "u_emissive",
+                MaterialParam::Vec3(Vector3::new(0.0, 0.0, 0.0)),
+            )
+    }
+
+    pub fn with_texture(mut self, slot: &str, texture: Rc<Texture>) -> Self {
+        self.textures.insert(slot.to_string(), texture);
+        self
+    }
+
+    pub fn with_param(mut self, name: &str, value: MaterialParam) -> Self {
+        self.params.insert(name.to_string(), value);
+        self
+    }
+
+    pub fn with_flags(mut self, flags: RenderFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    pub fn texture(&self, slot: &str) -> Option<&Rc<Texture>> {
+        self.textures.get(slot)
+    }
+
+    pub fn vec3(&self, name: &str) -> Option<Vector3<f32>> {
+        match self.params.get(name) {
+            Some(MaterialParam::Vec3(v)) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// Włącza program i ustawia wszystkie tekstury, parametry i flagi
+    pub fn apply(&self) {
+        let program = &self.program;
+        program.use_program();
+
+        for (unit, (slot, texture)) in self.textures.iter().enumerate() {
+            texture.bind(unit as u32);
+            program.set_int(slot, unit as i32);
+        }
+
+        for (name, value) in &self.params {
+            set_param(program, name, value);
+        }
+
+        program.set_int(
+            "u_alpha_cutout",
+            (self.flags.mode == RenderMode::Cutout) as i32,
+        );
+        program.set_int(
+            "u_alpha_blend",
+            (self.flags.mode == RenderMode::Blended) as i32,
+        );
+    }
+}
+
+pub fn set_param(program: &Program, name: &str, value: &MaterialParam) {
+    match value {
+        MaterialParam::Float(v) => program.set_float(name, *v),
+        MaterialParam::Int(v) => program.set_int(name, *v),
+        MaterialParam::Vec3(v) => program.set_vec3(name, v),
+    }
+}
@@ -1,78 +1,184 @@
+use std::collections::HashMap;
+use std::fmt;
 use std::mem;
 use std::os::raw::c_void;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::ptr;
+use std::rc::Rc;
 
+use cgmath::{Point3, Vector2};
 use tobj;
 
+use crate::bounds::Bounds;
+
+/// Liczba floatów na wierzchołek: pos(3), normal(3), tex(2)
+const VERTEX_SIZE: usize = 8;
+
 pub struct Mesh {
     pub vao: u32,
     pub vbo: u32,
+    pub ebo: u32,
+    /// Liczba unikalnych wierzchołków w VBO
     pub vertex_count: i32,
+    /// Liczba wierzchołków przed złączeniem duplikatów (po 3 na trójkąt z OBJ)
+    pub unwelded_vertex_count: i32,
+    /// Liczba indeksów w EBO (3 na trójkąt)
+    pub index_count: i32,
+    /// AABB i sfera otaczająca w przestrzeni modelu
+    pub bounds: Bounds,
+    /// Kopia pozycji, UV i indeksów w pamięci CPU – do testów promienia (picking)
+    pub positions: Vec<Point3<f32>>,
+    pub tex_coords: Vec<Vector2<f32>>,
+    pub indices: Vec<u32>,
 }
 
-impl Mesh {
-    pub fn from_obj<P: AsRef<Path>>(path: P) -> Mesh {
-        let path_ref = path.as_ref();
-        println!("Loading OBJ: {:?}", path_ref);
-        println!("CWD: {:?}", std::env::current_dir().unwrap());
-        println!("Trying to load: {:?}", path_ref);
-        println!("Exists? {}", path_ref.exists());
-
-        let (models, _materials) = tobj::load_obj(path_ref)
-            .unwrap_or_else(|e| panic!("Failed to load OBJ {:?}: {:?}", path_ref, e));
+#[derive(Debug)]
+pub enum MeshError {
+    Load {
+        path: PathBuf,
+        source: tobj::LoadError,
+    },
+    Empty {
+        path: PathBuf,
+    },
+}
 
-        if models.is_empty() {
-            panic!("OBJ file has no models!");
+impl fmt::Display for MeshError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MeshError::Load { path, source } => {
+                write!(f, "failed to load OBJ {:?}: {}", path, source)
+            }
+            MeshError::Empty { path } => write!(f, "OBJ file {:?} has no models", path),
         }
+    }
+}
+
+impl std::error::Error for MeshError {}
+
+/// Materiał z pliku MTL (ścieżki tekstur względne do katalogu OBJ)
+#[derive(Debug, Clone)]
+pub struct ObjMaterial {
+    pub name: String,
+    pub diffuse: [f32; 3],
+    pub diffuse_texture: Option<PathBuf>,
+    pub normal_texture: Option<PathBuf>,
+    pub alpha: f32,
+    /// Rozszerzenia PBR z eksportu Blendera (Pr, Pm, Ke, map_Pr, ...)
+    pub pbr: Option<ObjPbr>,
+}
 
-        let mesh = &models[0].mesh;
+#[derive(Debug, Clone)]
+pub struct ObjPbr {
+    pub roughness: f32,
+    pub metallic: f32,
+    pub emissive: [f32; 3],
+    pub roughness_texture: Option<PathBuf>,
+    pub metallic_texture: Option<PathBuf>,
+    pub emissive_texture: Option<PathBuf>,
+}
+
+/// Pojedynczy obiekt/grupa z pliku OBJ razem z przypisanym materiałem
+#[derive(Clone)]
+pub struct ObjPart {
+    pub name: String,
+    pub mesh: Rc<Mesh>,
+    pub material: Option<ObjMaterial>,
+}
+
+impl Mesh {
+    /// Wczytuje wszystkie obiekty z pliku OBJ jako jedną siatkę
+    pub fn from_obj<P: AsRef<Path>>(path: P) -> Result<Mesh, MeshError> {
+        let (models, _materials) = load_obj(path.as_ref())?;
 
         let mut vertices: Vec<f32> = Vec::new();
-        for &i in &mesh.indices {
-            let i = i as usize;
-
-            let px = mesh.positions[3 * i];
-            let py = mesh.positions[3 * i + 1];
-            let pz = mesh.positions[3 * i + 2];
-
-            let (nx, ny, nz) = if !mesh.normals.is_empty() {
-                (
-                    mesh.normals[3 * i],
-                    mesh.normals[3 * i + 1],
-                    mesh.normals[3 * i + 2],
-                )
-            } else {
-                (0.0, 1.0, 0.0)
-            };
+        for model in &models {
+            vertices.extend(obj_vertices(&model.mesh));
+        }
 
-            let (tx, ty) = if !mesh.texcoords.is_empty() {
-                (mesh.texcoords[2 * i], mesh.texcoords[2 * i + 1])
+        Ok(Mesh::from_vertices(&vertices))
+    }
+
+    /// Wczytuje każdy obiekt z pliku OBJ osobno, razem z materiałami z MTL
+    pub fn from_obj_parts<P: AsRef<Path>>(path: P) -> Result<Vec<ObjPart>, MeshError> {
+        let path_ref = path.as_ref();
+        let (models, materials) = load_obj(path_ref)?;
+        let base_dir = path_ref.parent().unwrap_or_else(|| Path::new(""));
+
+        let texture_path = |name: &str| {
+            if name.is_empty() {
+                None
             } else {
-                (0.0, 0.0)
-            };
+                Some(base_dir.join(name))
+            }
+        };
+
+        let materials: Vec<ObjMaterial> = materials
+            .iter()
+            .map(|m| ObjMaterial {
+                name: m.name.clone(),
+                diffuse: m.diffuse,
+                diffuse_texture: texture_path(&m.diffuse_texture),
+                normal_texture: texture_path(&m.normal_texture)
+                    .or_else(|| bump_texture(m, &texture_path)),
+                alpha: m.dissolve,
+                pbr: obj_pbr(m, &texture_path),
+            })
+            .collect();
+
+        Ok(models
+            .iter()
+            .map(|model| ObjPart {
+                name: model.name.clone(),
+                mesh: Rc::new(Mesh::from_vertices(&obj_vertices(&model.mesh))),
+                material: model
+                    .mesh
+                    .material_id
+                    .and_then(|id| materials.get(id).cloned()),
+            })
+            .collect())
+    }
 
-            vertices.extend_from_slice(&[px, py, pz, nx, ny, nz, tx, ty]);
-        }
+    /// Tworzy siatkę z przeplecionych wierzchołków pos(3), normal(3), tex(2),
+    /// sklejając identyczne wierzchołki w jeden
+    pub fn from_vertices(vertices: &[f32]) -> Mesh {
+        let (welded, indices) = weld_vertices(vertices);
+        let mut mesh = Mesh::from_indexed(&welded, &indices);
+        mesh.unwelded_vertex_count = (vertices.len() / VERTEX_SIZE) as i32;
+        mesh
+    }
 
+    /// Tworzy VAO/VBO/EBO z unikalnych wierzchołków i listy indeksów
+    pub fn from_indexed(vertices: &[f32], indices: &[u32]) -> Mesh {
         let mut vao: u32 = 0;
         let mut vbo: u32 = 0;
+        let mut ebo: u32 = 0;
 
         unsafe {
             gl::GenVertexArrays(1, &mut vao);
             gl::GenBuffers(1, &mut vbo);
+            gl::GenBuffers(1, &mut ebo);
 
             gl::BindVertexArray(vao);
             gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
 
             gl::BufferData(
                 gl::ARRAY_BUFFER,
-                (vertices.len() * mem::size_of::<f32>()) as isize,
+                mem::size_of_val(vertices) as isize,
                 vertices.as_ptr() as *const c_void,
                 gl::STATIC_DRAW,
             );
 
-            let stride = (8 * mem::size_of::<f32>()) as i32;
+            // EBO zostaje przypięty do VAO
+            gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, ebo);
+            gl::BufferData(
+                gl::ELEMENT_ARRAY_BUFFER,
+                mem::size_of_val(indices) as isize,
+                indices.as_ptr() as *const c_void,
+                gl::STATIC_DRAW,
+            );
+
+            let stride = (VERTEX_SIZE * mem::size_of::<f32>()) as i32;
 
             // position
             gl::EnableVertexAttribArray(0);
@@ -106,16 +212,61 @@ impl Mesh {
         Mesh {
             vao,
             vbo,
-            vertex_count: mesh.indices.len() as i32,
+            ebo,
+            vertex_count: (vertices.len() / VERTEX_SIZE) as i32,
+            unwelded_vertex_count: (vertices.len() / VERTEX_SIZE) as i32,
+            index_count: indices.len() as i32,
+            bounds: Bounds::from_vertices(vertices, VERTEX_SIZE),
+            positions: vertices
+                .chunks_exact(VERTEX_SIZE)
+                .map(|v| Point3::new(v[0], v[1], v[2]))
+                .collect(),
+            tex_coords: vertices
+                .chunks_exact(VERTEX_SIZE)
+                .map(|v| Vector2::new(v[6], v[7]))
+                .collect(),
+            indices: indices.to_vec(),
         }
     }
 
-    pub fn draw(&self) {
-        unsafe {
-            gl::BindVertexArray(self.vao);
-            gl::DrawArrays(gl::TRIANGLES, 0, self.vertex_count);
-            gl::BindVertexArray(0);
+    /// Sześcian 1x1x1 stojący na ziemi – zastępuje siatki, których nie udało się wczytać
+    pub fn placeholder() -> Mesh {
+        // (normalna, oś U, oś V) dla każdej ściany
+        let faces: [([f32; 3], [f32; 3], [f32; 3]); 6] = [
+            ([1.0, 0.0, 0.0], [0.0, 0.0, -1.0], [0.0, 1.0, 0.0]),
+            ([-1.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 1.0, 0.0]),
+            ([0.0, 1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, -1.0]),
+            ([0.0, -1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]),
+            ([0.0, 0.0, 1.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+            ([0.0, 0.0, -1.0], [-1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+        ];
+
+        let mut vertices: Vec<f32> = Vec::with_capacity(6 * 4 * VERTEX_SIZE);
+        let mut indices: Vec<u32> = Vec::with_capacity(6 * 6);
+
+        for (n, u, v) in faces {
+            let base = (vertices.len() / VERTEX_SIZE) as u32;
+            for (su, sv) in [(-0.5, -0.5), (0.5, -0.5), (0.5, 0.5), (-0.5, 0.5)] {
+                let p = [
+                    n[0] * 0.5 + u[0] * su + v[0] * sv,
+                    n[1] * 0.5 + u[1] * su + v[1] * sv + 0.5,
+                    n[2] * 0.5 + u[2] * su + v[2] * sv,
+                ];
+                vertices.extend_from_slice(&[
+                    p[0],
+                    p[1],
+                    p[2],
+                    n[0],
+                    n[1],
+                    n[2],
+                    su + 0.5,
+                    sv + 0.5,
+                ]);
+            }
+            indices.extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
         }
+
+        Mesh::from_indexed(&vertices, &indices)
     }
 
     pub fn quad() -> Mesh {
@@ -126,55 +277,138 @@ impl Mesh {
             0.0, 0.0, 0.0, 1.0, 1.0, 1.0, -0.5, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 1.0,
         ];
 
-        let mut vao = 0;
-        let mut vbo = 0;
+        Mesh::from_vertices(&vertices)
+    }
+}
 
+impl Drop for Mesh {
+    fn drop(&mut self) {
         unsafe {
-            gl::GenVertexArrays(1, &mut vao);
-            gl::GenBuffers(1, &mut vbo);
+            gl::DeleteVertexArrays(1, &self.vao);
+            gl::DeleteBuffers(1, &self.vbo);
+            gl::DeleteBuffers(1, &self.ebo);
+        }
+    }
+}
 
-            gl::BindVertexArray(vao);
-            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+/// Blender zapisuje mapę normalnych jako `map_Bump [-bm 1.0] plik`, czego tobj nie rozpoznaje
+fn bump_texture(
+    material: &tobj::Material,
+    texture_path: &impl Fn(&str) -> Option<PathBuf>,
+) -> Option<PathBuf> {
+    ["map_Bump", "map_bump", "bump"]
+        .iter()
+        .find_map(|key| material.unknown_param.get(*key))
+        .and_then(|value| value.split_whitespace().last())
+        .and_then(texture_path)
+}
 
-            gl::BufferData(
-                gl::ARRAY_BUFFER,
-                (vertices.len() * std::mem::size_of::<f32>()) as isize,
-                vertices.as_ptr() as *const _,
-                gl::STATIC_DRAW,
-            );
+/// Parametry PBR z nieznanych tobj kluczy MTL; `None`, gdy materiał ich nie ma
+fn obj_pbr(
+    material: &tobj::Material,
+    texture_path: &impl Fn(&str) -> Option<PathBuf>,
+) -> Option<ObjPbr> {
+    let params = &material.unknown_param;
+    if !params.contains_key("Pr") && !params.contains_key("Pm") {
+        return None;
+    }
 
-            let stride = (8 * std::mem::size_of::<f32>()) as i32;
+    let float = |key: &str, default: f32| {
+        params
+            .get(key)
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(default)
+    };
+    let emissive = params
+        .get("Ke")
+        .map(|v| {
+            let mut rgb = [0.0; 3];
+            for (c, value) in rgb.iter_mut().zip(v.split_whitespace()) {
+                *c = value.parse().unwrap_or(0.0);
+            }
+            rgb
+        })
+        .unwrap_or([0.0; 3]);
+    let texture = |key: &str| params.get(key).and_then(|v| texture_path(v.trim()));
+
+    Some(ObjPbr {
+        roughness: float("Pr", 0.5),
+        metallic: float("Pm", 0.0),
+        emissive,
+        roughness_texture: texture("map_Pr"),
+        metallic_texture: texture("map_Pm"),
+        emissive_texture: texture("map_Ke"),
+    })
+}
 
-            gl::EnableVertexAttribArray(0);
-            gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, stride, std::ptr::null());
+fn load_obj(path: &Path) -> Result<(Vec<tobj::Model>, Vec<tobj::Material>), MeshError> {
+    println!("Loading OBJ: {:?}", path);
 
-            gl::EnableVertexAttribArray(1);
-            gl::VertexAttribPointer(
-                1,
-                3,
-                gl::FLOAT,
-                gl::FALSE,
-                stride,
-                (3 * std::mem::size_of::<f32>()) as *const _,
-            );
+    let (models, materials) = tobj::load_obj(path).map_err(|source| MeshError::Load {
+        path: path.to_path_buf(),
+        source,
+    })?;
 
-            gl::EnableVertexAttribArray(2);
-            gl::VertexAttribPointer(
-                2,
-                2,
-                gl::FLOAT,
-                gl::FALSE,
-                stride,
-                (6 * std::mem::size_of::<f32>()) as *const _,
-            );
+    if models.is_empty() {
+        return Err(MeshError::Empty {
+            path: path.to_path_buf(),
+        });
+    }
 
-            gl::BindVertexArray(0);
-        }
+    Ok((models, materials))
+}
 
-        Mesh {
-            vao,
-            vbo: vbo, // jeśli używasz `_vbo` jak wcześniej
-            vertex_count: 6,
+/// Rozwija indeksy siatki tobj do płaskiej tablicy wierzchołków
+fn obj_vertices(mesh: &tobj::Mesh) -> Vec<f32> {
+    let mut vertices: Vec<f32> = Vec::with_capacity(mesh.indices.len() * VERTEX_SIZE);
+    for &i in &mesh.indices {
+        let i = i as usize;
+
+        let px = mesh.positions[3 * i];
+        let py = mesh.positions[3 * i + 1];
+        let pz = mesh.positions[3 * i + 2];
+
+        let (nx, ny, nz) = if !mesh.normals.is_empty() {
+            (
+                mesh.normals[3 * i],
+                mesh.normals[3 * i + 1],
+                mesh.normals[3 * i + 2],
+            )
+        } else {
+            (0.0, 1.0, 0.0)
+        };
+
+        let (tx, ty) = if !mesh.texcoords.is_empty() {
+            (mesh.texcoords[2 * i], mesh.texcoords[2 * i + 1])
+        } else {
+            (0.0, 0.0)
+        };
+
+        vertices.extend_from_slice(&[px, py, pz, nx, ny, nz, tx, ty]);
+    }
+    vertices
+}
+
+/// Skleja wierzchołki o identycznych pozycji/normalnej/UV.
+/// Zwraca unikalne wierzchołki i indeksy odtwarzające oryginalną kolejność.
+fn weld_vertices(vertices: &[f32]) -> (Vec<f32>, Vec<u32>) {
+    let mut unique: Vec<f32> = Vec::new();
+    let mut indices: Vec<u32> = Vec::with_capacity(vertices.len() / VERTEX_SIZE);
+    let mut lookup: HashMap<[u32; VERTEX_SIZE], u32> = HashMap::new();
+
+    for vertex in vertices.chunks_exact(VERTEX_SIZE) {
+        // porównujemy bity floatów, -0.0 traktujemy jak 0.0
+        let mut key = [0u32; VERTEX_SIZE];
+        for (k, v) in key.iter_mut().zip(vertex) {
+            *k = if *v == 0.0 { 0 } else { v.to_bits() };
         }
+
+        let index = *lookup.entry(key).or_insert_with(|| {
+            unique.extend_from_slice(vertex);
+            (unique.len() / VERTEX_SIZE - 1) as u32
+        });
+        indices.push(index);
     }
+
+    (unique, indices)
 }
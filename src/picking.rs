@@ -0,0 +1,212 @@
+use cgmath::{
+    EuclideanSpace, InnerSpace, Matrix, Matrix3, Matrix4, Point3, SquareMatrix, Transform, Vector3,
+    Vector4,
+};
+
+use crate::bounds::Aabb;
+use crate::components::Renderable;
+use crate::ecs::{Entity, World};
+use crate::material::{DIFFUSE_SLOT, RenderMode};
+use crate::scene_graph::WorldTransform;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ray {
+    pub origin: Point3<f32>,
+    pub direction: Vector3<f32>,
+}
+
+impl Ray {
+    /// Promień z oka kamery przez punkt okna. `cursor` i `size` w tych samych
+    /// jednostkach (np. z `get_cursor_pos`/`get_size`), (0, 0) w lewym górnym rogu.
+    pub fn from_cursor(
+        cursor: (f64, f64),
+        size: (i32, i32),
+        view: &Matrix4<f32>,
+        proj: &Matrix4<f32>,
+    ) -> Option<Ray> {
+        let x = (2.0 * cursor.0 / size.0.max(1) as f64 - 1.0) as f32;
+        let y = (1.0 - 2.0 * cursor.1 / size.1.max(1) as f64) as f32;
+        let inverse = (proj * view).invert()?;
+
+        let unproject = |z: f32| {
+            let p = inverse * Vector4::new(x, y, z, 1.0);
+            Point3::from_vec(p.truncate() / p.w)
+        };
+        let near = unproject(-1.0);
+        let far = unproject(1.0);
+        // początek w oku, nie na bliskiej płaszczyźnie – `t` to odległość od kamery
+        let eye = view.invert()?.transform_point(Point3::origin());
+
+        Some(Ray {
+            origin: eye,
+            direction: (far - near).normalize(),
+        })
+    }
+
+    pub fn at(&self, t: f32) -> Point3<f32> {
+        self.origin + self.direction * t
+    }
+
+    /// Promień w innej przestrzeni. Kierunek nie jest normalizowany, więc
+    /// parametr `t` trafienia jest taki sam w obu przestrzeniach.
+    pub fn transformed(&self, matrix: &Matrix4<f32>) -> Ray {
+        Ray {
+            origin: matrix.transform_point(self.origin),
+            direction: matrix.transform_vector(self.direction),
+        }
+    }
+
+    /// Punkt przecięcia z płaszczyzną przed początkiem promienia
+    pub fn intersect_plane(&self, point: Point3<f32>, normal: Vector3<f32>) -> Option<Point3<f32>> {
+        let denom = normal.dot(self.direction);
+        if denom.abs() < 1e-4 {
+            return None;
+        }
+        let t = normal.dot(point - self.origin) / denom;
+        (t >= 0.0).then(|| self.at(t))
+    }
+
+    /// Parametr punktu prostej `origin + s * axis` najbliższego promieniowi;
+    /// `None`, gdy są prawie równoległe
+    pub fn closest_on_line(&self, origin: Point3<f32>, axis: Vector3<f32>) -> Option<f32> {
+        let w = origin - self.origin;
+        let a = axis.dot(axis);
+        let b = axis.dot(self.direction);
+        let c = self.direction.dot(self.direction);
+        let d = axis.dot(w);
+        let e = self.direction.dot(w);
+        let denom = a * c - b * b;
+        if denom.abs() < 1e-6 * a * c {
+            return None;
+        }
+        Some((b * e - c * d) / denom)
+    }
+
+    /// Metoda "slab"; zwraca `t` wejścia (0, gdy początek jest w środku)
+    pub fn intersect_aabb(&self, aabb: &Aabb) -> Option<f32> {
+        let mut t_min = 0.0f32;
+        let mut t_max = f32::INFINITY;
+        for axis in 0..3 {
+            let inv = 1.0 / self.direction[axis];
+            let t1 = (aabb.min[axis] - self.origin[axis]) * inv;
+            let t2 = (aabb.max[axis] - self.origin[axis]) * inv;
+            // NaN (promień równoległy, początek na ściance) nie zawęża przedziału
+            t_min = t_min.max(t1.min(t2));
+            t_max = t_max.min(t1.max(t2));
+        }
+        (t_min <= t_max).then_some(t_min)
+    }
+
+    /// Möller–Trumbore, trafienia z obu stron trójkąta. Zwraca `t` i współrzędne
+    /// barycentryczne `(u, v)` punktu (waga `b` i `c`)
+    pub fn intersect_triangle(
+        &self,
+        a: Point3<f32>,
+        b: Point3<f32>,
+        c: Point3<f32>,
+    ) -> Option<(f32, f32, f32)> {
+        let edge1 = b - a;
+        let edge2 = c - a;
+        let p = self.direction.cross(edge2);
+        let det = edge1.dot(p);
+        if det.abs() < 1e-8 {
+            return None;
+        }
+
+        let inv_det = 1.0 / det;
+        let s = self.origin - a;
+        let u = s.dot(p) * inv_det;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+        let q = s.cross(edge1);
+        let v = self.direction.dot(q) * inv_det;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+
+        let t = edge2.dot(q) * inv_det;
+        (t > 0.0).then_some((t, u, v))
+    }
+}
+
+/// Wynik trafienia promieniem w encję
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hit {
+    pub entity: Entity,
+    pub position: Point3<f32>,
+    /// Normalna ściany w przestrzeni świata, zwrócona w stronę promienia
+    pub normal: Vector3<f32>,
+    /// Odległość od początku promienia (dla `Ray::from_cursor` – od oka kamery)
+    pub distance: f32,
+}
+
+/// Najbliższa encja z `Renderable` trafiona promieniem: najpierw test AABB
+/// siatki, potem jej trójkąty (wszystko w przestrzeni modelu). Materiały
+/// `Cutout` przepuszczają promień tam, gdzie alfa tekstury `DIFFUSE_SLOT` < 0.5
+pub fn pick(world: &World, ray: &Ray) -> Option<Hit> {
+    let mut best: Option<Hit> = None;
+
+    for (entity, (renderable, WorldTransform(model))) in
+        world.query::<(&Renderable, &WorldTransform)>()
+    {
+        let Some(inverse) = model.invert() else {
+            continue;
+        };
+        let local = ray.transformed(&inverse);
+        let mesh = &renderable.mesh;
+        let cutout = match renderable.material.flags.mode {
+            RenderMode::Cutout => renderable.material.textures.get(DIFFUSE_SLOT),
+            _ => None,
+        };
+
+        let closest = best.map_or(f32::INFINITY, |hit| hit.distance);
+        match local.intersect_aabb(&mesh.bounds.aabb) {
+            Some(t) if t < closest => {}
+            _ => continue,
+        }
+
+        let mut nearest: Option<(f32, Vector3<f32>)> = None;
+        for triangle in mesh.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| mesh.positions[triangle[i] as usize]);
+            let Some((t, u, v)) = local.intersect_triangle(a, b, c) else {
+                continue;
+            };
+            if t >= nearest.map_or(closest, |(best_t, _)| best_t) {
+                continue;
+            }
+            if let Some(texture) = cutout {
+                let [ta, tb, tc] = [0, 1, 2].map(|i| mesh.tex_coords[triangle[i] as usize]);
+                if texture.alpha_at(ta * (1.0 - u - v) + tb * u + tc * v) < 0.5 {
+                    continue;
+                }
+            }
+            nearest = Some((t, (b - a).cross(c - a)));
+        }
+
+        if let Some((t, local_normal)) = nearest {
+            // normalne przekształca się odwrotnością transpozycji
+            let normal_matrix = upper_left(&inverse).transpose();
+            let mut normal = (normal_matrix * local_normal).normalize();
+            if normal.dot(ray.direction) > 0.0 {
+                normal = -normal;
+            }
+            best = Some(Hit {
+                entity,
+                position: ray.at(t),
+                normal,
+                distance: t,
+            });
+        }
+    }
+
+    best
+}
+
+fn upper_left(matrix: &Matrix4<f32>) -> Matrix3<f32> {
+    Matrix3::from_cols(
+        matrix.x.truncate(),
+        matrix.y.truncate(),
+        matrix.z.truncate(),
+    )
+}
@@ -0,0 +1,125 @@
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+use cgmath::{ElementWise, EuclideanSpace, InnerSpace, Point3, Vector3};
+
+use crate::bounds::Frustum;
+use crate::components::{ColorAnimation, Renderable};
+use crate::ecs::{Entity, World};
+use crate::instancing::{self, Batch, DrawItem, InstanceData};
+use crate::material::RenderMode;
+use crate::scene_graph::WorldTransform;
+
+/// Partie jednej klatki rozdzielone na przebiegi, w kolejności rysowania
+pub struct RenderQueue<'a> {
+    pub opaque: Vec<Batch<'a>>,
+    pub cutout: Vec<Batch<'a>>,
+    pub blended: Vec<Batch<'a>>,
+    /// Encje odrzucone przez test widoczności
+    pub culled: usize,
+}
+
+/// Mnożnik koloru zaznaczonej encji
+const HIGHLIGHT_TINT: Vector3<f32> = Vector3 {
+    x: 1.6,
+    y: 1.4,
+    z: 0.5,
+};
+
+/// Encja do narysowania z odległością od kamery
+struct Item<'a> {
+    draw: DrawItem<'a>,
+    distance: f32,
+}
+
+impl<'a> RenderQueue<'a> {
+    /// Nieprzezroczyste i cutout: posortowane po programie, teksturze i siatce
+    /// (mniej zmian stanu), a w ramach tego od najbliższych. Przezroczyste:
+    /// od najdalszych, łączone w partie tylko gdy sąsiadują w tej kolejności.
+    /// Encje, których bryły otaczające są poza `frustum`, są pomijane.
+    /// Encja `selected` jest rozjaśniona.
+    pub fn build(
+        world: &'a World,
+        eye: Point3<f32>,
+        frustum: &Frustum,
+        selected: Option<Entity>,
+    ) -> Self {
+        let mut opaque = Vec::new();
+        let mut cutout = Vec::new();
+        let mut blended = Vec::new();
+        let mut culled = 0;
+
+        let drawable = world.query::<(&Renderable, &WorldTransform, Option<&ColorAnimation>)>();
+        for (entity, (renderable, WorldTransform(model), color_animation)) in drawable {
+            if !frustum.intersects(&renderable.mesh.bounds.transformed(model)) {
+                culled += 1;
+                continue;
+            }
+
+            let mut tint = renderable.tint;
+            if selected == Some(entity) {
+                tint = tint.mul_element_wise(HIGHLIGHT_TINT);
+            }
+            let item = Item {
+                draw: DrawItem {
+                    renderable,
+                    color_animation,
+                    instance: InstanceData::new(*model, tint),
+                },
+                distance: (model.w.truncate() - eye.to_vec()).magnitude2(),
+            };
+            match renderable.material.flags.mode {
+                RenderMode::Opaque => opaque.push(item),
+                RenderMode::Cutout => cutout.push(item),
+                RenderMode::Blended => blended.push(item),
+            }
+        }
+
+        opaque.sort_by(state_then_distance);
+        cutout.sort_by(state_then_distance);
+        blended.sort_by(|a, b| b.distance.total_cmp(&a.distance));
+
+        RenderQueue {
+            opaque: instancing::build_batches(opaque.into_iter().map(|item| item.draw), true),
+            cutout: instancing::build_batches(cutout.into_iter().map(|item| item.draw), true),
+            blended: instancing::build_ordered_batches(blended.into_iter().map(|item| item.draw)),
+            culled,
+        }
+    }
+
+    /// Liczba narysowanych obiektów (egzemplarzy we wszystkich partiach)
+    pub fn drawn(&self) -> usize {
+        [&self.opaque, &self.cutout, &self.blended]
+            .into_iter()
+            .flatten()
+            .map(|batch| batch.instances.len())
+            .sum()
+    }
+
+    /// Liczba wywołań rysowania w przebiegach: nieprzezroczystym, cutout, przezroczystym
+    pub fn draw_calls(&self) -> [usize; 3] {
+        [self.opaque.len(), self.cutout.len(), self.blended.len()]
+    }
+}
+
+fn state_then_distance(a: &Item, b: &Item) -> Ordering {
+    state_key(a.draw.renderable)
+        .cmp(&state_key(b.draw.renderable))
+        .then(a.distance.total_cmp(&b.distance))
+}
+
+/// Klucz zmian stanu: program, pierwsza tekstura, siatka, materiał
+fn state_key(renderable: &Renderable) -> (usize, usize, usize, usize) {
+    let material = &renderable.material;
+    let texture = material
+        .textures
+        .values()
+        .next()
+        .map_or(0, |texture| Rc::as_ptr(texture) as usize);
+    (
+        Rc::as_ptr(&material.program) as usize,
+        texture,
+        Rc::as_ptr(&renderable.mesh) as usize,
+        Rc::as_ptr(material) as usize,
+    )
+}
@@ -0,0 +1,1316 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use cgmath::{Deg, Euler, Point3, Quaternion, Rad, Rotation3, Vector3};
+use rand::Rng;
+use ron::extensions::Extensions;
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+
+use crate::animation::{
+    AnimationClip, Animator, Easing, Interpolation, Keyable, Keyframe, PlaybackMode, Track,
+};
+use crate::assets::{self, AssetManager};
+use crate::components::{ColorAnimation, Name, Renderable, RotationAnimation};
+use crate::ecs::{Entity, World};
+use crate::light::{Light, LightKind};
+use crate::material::{
+    BASIC_FRAG, BASIC_VERT, Material, MaterialParam, PBR_FRAG, PBR_VERT, RenderFlags, RenderMode,
+};
+use crate::mesh::Mesh;
+use crate::scene_graph::{self, Parent, Transform};
+use crate::shader::Program;
+use crate::textures::{Texture, Wrap};
+
+/// Scena wczytywana bez `--scene`; edytor jej nie nadpisuje
+pub const DEFAULT_SCENE: &str = "assets/scenes/default.ron";
+
+/// Opis sceny wczytywany z pliku RON (np. assets/scenes/default.ron)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SceneDesc {
+    #[serde(default)]
+    pub meshes: BTreeMap<String, MeshSource>,
+    #[serde(default)]
+    pub textures: BTreeMap<String, TextureDesc>,
+    #[serde(default)]
+    pub materials: BTreeMap<String, MaterialDesc>,
+    #[serde(default)]
+    pub scatter: Vec<ScatterDesc>,
+    #[serde(default)]
+    pub objects: Vec<ObjectDesc>,
+    #[serde(default)]
+    pub models: Vec<ModelDesc>,
+    /// Bez świateł w pliku scena dostaje `Light::sun()`
+    #[serde(default)]
+    pub lights: Vec<LightDesc>,
+    #[serde(default = "default_ambient")]
+    pub ambient: [f32; 3],
+}
+
+/// Wczytana scena gotowa do rysowania
+pub struct Scene {
+    pub world: World,
+    pub lights: Vec<Light>,
+    pub ambient: Vector3<f32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum MeshSource {
+    Obj(String),
+    Quad,
+    /// Jedna część pliku OBJ wczytanego jako model (po nazwie części)
+    ObjPart {
+        path: String,
+        part: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextureDesc {
+    pub path: String,
+    /// Zawijanie z odbiciem; ta sama ścieżka bez flagi to osobna tekstura
+    #[serde(default)]
+    pub mirrored_repeat: bool,
+}
+
+/// Materiał współdzielony przez obiekty, które podają go w `material`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaterialDesc {
+    #[serde(default = "basic_vert")]
+    pub vert: String,
+    #[serde(default = "basic_frag")]
+    pub frag: String,
+    /// Slot (nazwa samplera w shaderze) -> nazwa tekstury z `textures`
+    #[serde(default)]
+    pub textures: BTreeMap<String, String>,
+    #[serde(default)]
+    pub params: BTreeMap<String, ParamDesc>,
+    #[serde(default)]
+    pub alpha_cutout: bool,
+    /// Mieszanie z alfą (parametr `u_opacity`), ma pierwszeństwo przed `alpha_cutout`
+    #[serde(default)]
+    pub blended: bool,
+    #[serde(default = "yes")]
+    pub cast_shadows: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum ParamDesc {
+    Float(f32),
+    Int(i32),
+    Vec3([f32; 3]),
+}
+
+impl From<MaterialParam> for ParamDesc {
+    fn from(param: MaterialParam) -> Self {
+        match param {
+            MaterialParam::Float(v) => ParamDesc::Float(v),
+            MaterialParam::Int(v) => ParamDesc::Int(v),
+            MaterialParam::Vec3(v) => ParamDesc::Vec3(v.into()),
+        }
+    }
+}
+
+impl From<ParamDesc> for MaterialParam {
+    fn from(param: ParamDesc) -> Self {
+        match param {
+            ParamDesc::Float(v) => MaterialParam::Float(v),
+            ParamDesc::Int(v) => MaterialParam::Int(v),
+            ParamDesc::Vec3(v) => MaterialParam::Vec3(vec3(v)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct TransformDesc {
+    #[serde(default)]
+    pub translation: [f32; 3],
+    /// Kąty Eulera w stopniach (x, y, z)
+    #[serde(default)]
+    pub rotation: [f32; 3],
+    #[serde(default = "one")]
+    pub scale: [f32; 3],
+}
+
+impl Default for TransformDesc {
+    fn default() -> Self {
+        TransformDesc {
+            translation: [0.0; 3],
+            rotation: [0.0; 3],
+            scale: one(),
+        }
+    }
+}
+
+impl TransformDesc {
+    pub fn from_transform(transform: &Transform) -> Self {
+        TransformDesc {
+            translation: transform.translation.into(),
+            rotation: degrees(transform.rotation),
+            scale: transform.scale.into(),
+        }
+    }
+
+    pub fn transform(&self) -> Transform {
+        Transform::from_translation(vec3(self.translation))
+            .with_rotation(euler(self.rotation))
+            .with_scale(vec3(self.scale))
+    }
+}
+
+/// Klipy animacji klatkowej obiektu (komponent `Animator`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnimatorDesc {
+    pub clips: Vec<ClipDesc>,
+    #[serde(default)]
+    pub mode: PlaybackMode,
+    #[serde(default = "one_f32")]
+    pub speed: f32,
+    #[serde(default = "yes")]
+    pub playing: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClipDesc {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub translation: Option<TrackDesc>,
+    /// Klatki to kąty Eulera w stopniach (x, y, z)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rotation: Option<TrackDesc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scale: Option<TrackDesc>,
+    /// Mnożnik koloru egzemplarza
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<TrackDesc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackDesc {
+    #[serde(default)]
+    pub interpolation: Interpolation,
+    pub keys: Vec<KeyDesc>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct KeyDesc {
+    pub time: f32,
+    pub value: [f32; 3],
+    /// Krzywa odcinka do następnej klatki
+    #[serde(default)]
+    pub easing: Easing,
+}
+
+impl AnimatorDesc {
+    pub fn from_animator(animator: &Animator) -> Self {
+        AnimatorDesc {
+            clips: animator.clips.iter().map(ClipDesc::from_clip).collect(),
+            mode: animator.mode,
+            speed: animator.speed,
+            playing: animator.playing,
+        }
+    }
+
+    pub fn build(&self) -> Animator {
+        let clips = self.clips.iter().map(ClipDesc::build).collect();
+        Animator::new(clips)
+            .with_mode(self.mode)
+            .with_speed(self.speed)
+            .with_playing(self.playing)
+    }
+}
+
+impl ClipDesc {
+    fn from_clip(clip: &AnimationClip) -> Self {
+        let vector = |v: Vector3<f32>| v.into();
+        ClipDesc {
+            name: clip.name.clone(),
+            translation: clip
+                .translation
+                .as_ref()
+                .map(|t| TrackDesc::from_track(t, vector)),
+            rotation: clip
+                .rotation
+                .as_ref()
+                .map(|t| TrackDesc::from_track(t, degrees)),
+            scale: clip
+                .scale
+                .as_ref()
+                .map(|t| TrackDesc::from_track(t, vector)),
+            color: clip
+                .color
+                .as_ref()
+                .map(|t| TrackDesc::from_track(t, vector)),
+        }
+    }
+
+    fn build(&self) -> AnimationClip {
+        AnimationClip {
+            name: self.name.clone(),
+            translation: self.translation.as_ref().map(|track| track.build(vec3)),
+            rotation: self.rotation.as_ref().map(|track| track.build(euler)),
+            scale: self.scale.as_ref().map(|track| track.build(vec3)),
+            color: self.color.as_ref().map(|track| track.build(vec3)),
+        }
+    }
+}
+
+impl TrackDesc {
+    fn from_track<T: Keyable>(track: &Track<T>, value: impl Fn(T) -> [f32; 3]) -> Self {
+        let keys = track
+            .keys()
+            .iter()
+            .map(|key| KeyDesc {
+                time: key.time,
+                value: value(key.value),
+                easing: key.easing,
+            })
+            .collect();
+        TrackDesc {
+            interpolation: track.interpolation,
+            keys,
+        }
+    }
+
+    fn build<T: Keyable>(&self, value: impl Fn([f32; 3]) -> T) -> Track<T> {
+        let keys = self
+            .keys
+            .iter()
+            .map(|key| Keyframe {
+                time: key.time,
+                value: value(key.value),
+                easing: key.easing,
+            })
+            .collect();
+        Track::new(keys, self.interpolation)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct RotationAnimDesc {
+    pub axis: [f32; 3],
+    pub speed: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObjectDesc {
+    pub mesh: String,
+    /// Nazwa materiału z `materials`; bez niego materiał powstaje
+    /// z pól `texture`, `color1`, `color2`, `ground` i `alpha_cutout`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub material: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub texture: Option<String>,
+    #[serde(default)]
+    pub transform: TransformDesc,
+    #[serde(default = "one", skip_serializing_if = "is_one")]
+    pub color1: [f32; 3],
+    #[serde(default = "one", skip_serializing_if = "is_one")]
+    pub color2: [f32; 3],
+    /// Mnożnik koloru egzemplarza (`Renderable::tint`)
+    #[serde(default = "one", skip_serializing_if = "is_one")]
+    pub tint: [f32; 3],
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rotation: Option<RotationAnimDesc>,
+    /// Prędkość animacji koloru
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color_animation: Option<f32>,
+    /// Animacja klatkowa – nadpisuje `transform` i kolor egzemplarza
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub animation: Option<AnimatorDesc>,
+    /// Gradient wzdłuż osi X świata zamiast po normalnej
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub ground: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub alpha_cutout: bool,
+    /// Nieprzezroczystość < 1 włącza mieszanie z alfą
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub opacity: Option<f32>,
+    /// Nazwa, po której inne obiekty mogą wskazać ten jako rodzica
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Nazwa obiektu-rodzica; `transform` jest wtedy względem niego
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+}
+
+/// Model OBJ wczytany ze wszystkimi częściami i materiałami MTL
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelDesc {
+    pub path: String,
+    #[serde(default)]
+    pub transform: TransformDesc,
+}
+
+/// Losowe rozmieszczenie `count` egzemplarzy siatki na prostokącie XZ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScatterDesc {
+    pub mesh: String,
+    #[serde(default)]
+    pub material: Option<String>,
+    #[serde(default)]
+    pub texture: Option<String>,
+    #[serde(default)]
+    pub alpha_cutout: bool,
+    #[serde(default = "one")]
+    pub color1: [f32; 3],
+    #[serde(default = "one")]
+    pub color2: [f32; 3],
+    pub count: usize,
+    pub area_min: [f32; 2],
+    pub area_max: [f32; 2],
+    pub scale: [f32; 2],
+    /// Każdy egzemplarz to dwa quady skrzyżowane pod kątem 90°
+    #[serde(default)]
+    pub crossed: bool,
+    /// Losowa zmiana jasności egzemplarzy, np. 0.2 = ±20%
+    #[serde(default)]
+    pub tint_variation: f32,
+    /// Egzemplarze zapisane z edytora (po jednym na quad). Niepuste zastępuje
+    /// losowanie – `count`, `area_*`, `scale`, `crossed` i `tint_variation`
+    /// są wtedy pomijane
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub instances: Vec<InstanceDesc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstanceDesc {
+    #[serde(default)]
+    pub transform: TransformDesc,
+    #[serde(default = "one", skip_serializing_if = "is_one")]
+    pub tint: [f32; 3],
+}
+
+/// Egzemplarz z `scatter`. Zapis sceny oddaje opis rozsiewu; gdy któryś
+/// egzemplarz zmieniono albo usunięto – z listą `instances`
+pub struct Scattered {
+    pub scatter: Rc<ScatterDesc>,
+    /// Transformacja i odcień nadane przy wczytaniu
+    pub placed: Transform,
+    pub tint: Vector3<f32>,
+}
+
+/// Źródło światła; kąty stożka w stopniach
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum LightDesc {
+    Directional {
+        direction: [f32; 3],
+        #[serde(default = "one")]
+        color: [f32; 3],
+        #[serde(default = "one_f32")]
+        intensity: f32,
+    },
+    Point {
+        position: [f32; 3],
+        #[serde(default = "one")]
+        color: [f32; 3],
+        #[serde(default = "one_f32")]
+        intensity: f32,
+        range: f32,
+    },
+    Spot {
+        position: [f32; 3],
+        direction: [f32; 3],
+        #[serde(default = "one")]
+        color: [f32; 3],
+        #[serde(default = "one_f32")]
+        intensity: f32,
+        range: f32,
+        inner_angle: f32,
+        outer_angle: f32,
+    },
+}
+
+impl From<&Light> for LightDesc {
+    fn from(light: &Light) -> Self {
+        let position = light.position.into();
+        let direction = light.direction.into();
+        let color = light.color.into();
+        match light.kind {
+            LightKind::Directional => LightDesc::Directional {
+                direction,
+                color,
+                intensity: light.intensity,
+            },
+            LightKind::Point => LightDesc::Point {
+                position,
+                color,
+                intensity: light.intensity,
+                range: light.range,
+            },
+            LightKind::Spot => LightDesc::Spot {
+                position,
+                direction,
+                color,
+                intensity: light.intensity,
+                range: light.range,
+                inner_angle: light.inner_angle,
+                outer_angle: light.outer_angle,
+            },
+        }
+    }
+}
+
+impl LightDesc {
+    pub fn build(&self) -> Light {
+        match *self {
+            LightDesc::Directional {
+                direction,
+                color,
+                intensity,
+            } => Light::directional(vec3(direction), vec3(color), intensity),
+            LightDesc::Point {
+                position,
+                color,
+                intensity,
+                range,
+            } => Light::point(Point3::from(position), vec3(color), intensity, range),
+            LightDesc::Spot {
+                position,
+                direction,
+                color,
+                intensity,
+                range,
+                inner_angle,
+                outer_angle,
+            } => Light::spot(
+                Point3::from(position),
+                vec3(direction),
+                vec3(color),
+                intensity,
+                range,
+            )
+            .with_cone(inner_angle, outer_angle),
+        }
+    }
+}
+
+fn one() -> [f32; 3] {
+    [1.0; 3]
+}
+
+fn yes() -> bool {
+    true
+}
+
+fn is_one(v: &[f32; 3]) -> bool {
+    *v == one()
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+fn basic_vert() -> String {
+    BASIC_VERT.to_string()
+}
+
+fn basic_frag() -> String {
+    BASIC_FRAG.to_string()
+}
+
+fn one_f32() -> f32 {
+    1.0
+}
+
+/// Poziom światła otoczenia sprzed wprowadzenia świateł
+fn default_ambient() -> [f32; 3] {
+    [0.3; 3]
+}
+
+fn vec3(v: [f32; 3]) -> Vector3<f32> {
+    Vector3::new(v[0], v[1], v[2])
+}
+
+/// Kąty Eulera w stopniach – odwrotność `euler`
+fn degrees(rotation: Quaternion<f32>) -> [f32; 3] {
+    let angles = Euler::from(rotation);
+    [
+        Deg::from(angles.x).0,
+        Deg::from(angles.y).0,
+        Deg::from(angles.z).0,
+    ]
+}
+
+/// Kąty Eulera w stopniach
+fn euler([x, y, z]: [f32; 3]) -> Quaternion<f32> {
+    Quaternion::from(Euler::new(Deg(x), Deg(y), Deg(z)))
+}
+
+#[derive(Debug)]
+pub enum SceneError {
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    Parse {
+        path: PathBuf,
+        source: ron::error::SpannedError,
+    },
+    /// Poprawny RON z niepoprawnymi wartościami (np. odwrócony zakres)
+    Invalid {
+        path: PathBuf,
+        message: String,
+    },
+    Serialize(ron::Error),
+    Write {
+        path: PathBuf,
+        source: io::Error,
+    },
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SceneError::Io { path, source } => {
+                write!(f, "failed to read scene {:?}: {}", path, source)
+            }
+            SceneError::Parse { path, source } => {
+                write!(f, "failed to parse scene {:?}: {}", path, source)
+            }
+            SceneError::Invalid { path, message } => {
+                write!(f, "invalid scene {:?}: {}", path, message)
+            }
+            SceneError::Serialize(source) => write!(f, "failed to serialize scene: {}", source),
+            SceneError::Write { path, source } => {
+                write!(f, "failed to write scene {:?}: {}", path, source)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SceneError {}
+
+/// Czy `path` wskazuje `DEFAULT_SCENE` (też przez inną ścieżkę względną)
+pub fn is_default_scene(path: &Path) -> bool {
+    match (fs::canonicalize(path), fs::canonicalize(DEFAULT_SCENE)) {
+        (Ok(path), Ok(default)) => path == default,
+        _ => path == Path::new(DEFAULT_SCENE),
+    }
+}
+
+pub fn load_scene<P: AsRef<Path>>(path: P, assets: &mut AssetManager) -> Result<Scene, SceneError> {
+    let path_ref = path.as_ref();
+    println!("Loading scene: {:?}", path_ref);
+
+    let source = fs::read_to_string(path_ref).map_err(|source| SceneError::Io {
+        path: path_ref.to_path_buf(),
+        source,
+    })?;
+    let desc: SceneDesc = ron::from_str(&source).map_err(|source| SceneError::Parse {
+        path: path_ref.to_path_buf(),
+        source,
+    })?;
+    desc.validate().map_err(|message| SceneError::Invalid {
+        path: path_ref.to_path_buf(),
+        message,
+    })?;
+
+    let lights = if desc.lights.is_empty() {
+        vec![Light::sun()]
+    } else {
+        desc.lights.iter().map(LightDesc::build).collect()
+    };
+
+    Ok(Scene {
+        world: desc.build(assets),
+        lights,
+        ambient: vec3(desc.ambient),
+    })
+}
+
+/// Zapisuje scenę do pliku RON, który `load_scene` wczyta z powrotem
+/// w tej samej postaci (zob. `SceneDesc::capture`)
+pub fn save_scene<P: AsRef<Path>>(
+    path: P,
+    world: &World,
+    lights: &[Light],
+    ambient: Vector3<f32>,
+    assets: &AssetManager,
+) -> Result<(), SceneError> {
+    let path_ref = path.as_ref();
+    println!("Saving scene: {:?}", path_ref);
+
+    let desc = SceneDesc::capture(world, lights, ambient, assets);
+    let config = PrettyConfig::new().extensions(Extensions::IMPLICIT_SOME);
+    let source = ron::ser::to_string_pretty(&desc, config).map_err(SceneError::Serialize)?;
+    fs::write(path_ref, source).map_err(|source| SceneError::Write {
+        path: path_ref.to_path_buf(),
+        source,
+    })
+}
+
+impl SceneDesc {
+    /// Sprawdza wartości, których nie wyłapie parser: zakresy rozsiewu
+    /// muszą mieć `min <= max`, a `tint_variation` nie może być ujemne
+    pub fn validate(&self) -> Result<(), String> {
+        for scatter in &self.scatter {
+            let ranges = [
+                (
+                    "area_min/area_max X",
+                    scatter.area_min[0],
+                    scatter.area_max[0],
+                ),
+                (
+                    "area_min/area_max Z",
+                    scatter.area_min[1],
+                    scatter.area_max[1],
+                ),
+                ("scale", scatter.scale[0], scatter.scale[1]),
+            ];
+            for (field, low, high) in ranges {
+                if low > high {
+                    return Err(format!(
+                        "scatter of '{}': {} range {}..{} is inverted",
+                        scatter.mesh, field, low, high
+                    ));
+                }
+            }
+            if scatter.tint_variation < 0.0 {
+                return Err(format!(
+                    "scatter of '{}': tint_variation {} is negative",
+                    scatter.mesh, scatter.tint_variation
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Opis bieżącego stanu świata. Każda encja z `Transform` i `Renderable`
+    /// staje się obiektem z nazwanym materiałem, a siatki, tekstury i shadery –
+    /// ścieżkami z cache zasobów. Części modeli też są zwykłymi obiektami.
+    /// Nietknięty `scatter` zostaje opisem do losowania; edytowany dostaje
+    /// listę `instances` z transformacją i odcieniem każdego egzemplarza.
+    pub fn capture(
+        world: &World,
+        lights: &[Light],
+        ambient: Vector3<f32>,
+        assets: &AssetManager,
+    ) -> SceneDesc {
+        let mut capture = Capture {
+            assets,
+            desc: SceneDesc {
+                meshes: BTreeMap::new(),
+                textures: BTreeMap::new(),
+                materials: BTreeMap::new(),
+                scatter: Vec::new(),
+                objects: Vec::new(),
+                models: Vec::new(),
+                lights: lights.iter().map(LightDesc::from).collect(),
+                ambient: ambient.into(),
+            },
+            names: HashMap::new(),
+        };
+
+        let mut drawable = Vec::new();
+        // rozsiew -> (siatka i materiał pierwszego egzemplarza, egzemplarze)
+        type Instance<'w> = (&'w Transform, &'w Renderable, &'w Scattered);
+        let mut scattered: Vec<(&Renderable, Vec<Instance>)> = Vec::new();
+        for (entity, (transform, renderable, instance)) in
+            world.query::<(&Transform, &Renderable, Option<&Scattered>)>()
+        {
+            let Some(instance) = instance else {
+                drawable.push((entity, (transform, renderable)));
+                continue;
+            };
+            let group = scattered
+                .iter_mut()
+                .find(|(_, instances)| Rc::ptr_eq(&instances[0].2.scatter, &instance.scatter));
+            match group {
+                // egzemplarz z własną kopią materiału zapisujemy jako obiekt
+                Some((first, _))
+                    if !Rc::ptr_eq(&first.mesh, &renderable.mesh)
+                        || !Rc::ptr_eq(&first.material, &renderable.material) =>
+                {
+                    drawable.push((entity, (transform, renderable)));
+                }
+                Some((_, instances)) => instances.push((transform, renderable, instance)),
+                None => scattered.push((renderable, vec![(transform, renderable, instance)])),
+            }
+        }
+
+        for (first, instances) in scattered {
+            let scatter = &instances[0].2.scatter;
+            let placed = if scatter.instances.is_empty() {
+                scatter.count * if scatter.crossed { 2 } else { 1 }
+            } else {
+                scatter.instances.len()
+            };
+            let edited = instances.len() != placed
+                || instances.iter().any(|(transform, renderable, instance)| {
+                    **transform != instance.placed || renderable.tint != instance.tint
+                });
+            let desc = ScatterDesc {
+                mesh: capture.mesh(&first.mesh),
+                material: Some(capture.material(&first.material)),
+                texture: None,
+                alpha_cutout: false,
+                color1: one(),
+                color2: one(),
+                instances: if edited {
+                    instances
+                        .iter()
+                        .map(|(transform, renderable, _)| InstanceDesc {
+                            transform: TransformDesc::from_transform(transform),
+                            tint: renderable.tint.into(),
+                        })
+                        .collect()
+                } else {
+                    scatter.instances.clone()
+                },
+                ..ScatterDesc::clone(scatter)
+            };
+            capture.desc.scatter.push(desc);
+        }
+
+        // rodzice są wskazywani po nazwie, więc ich nazwy muszą być jednoznaczne
+        let mut name_count: HashMap<String, usize> = HashMap::new();
+        for (entity, _) in &drawable {
+            *name_count.entry(object_name(world, *entity)).or_default() += 1;
+        }
+        let parent_name = |entity: Entity| {
+            let name = object_name(world, entity);
+            if name_count.get(&name).copied().unwrap_or(0) > 1 {
+                format!("{} #{}", name, entity.index())
+            } else {
+                name
+            }
+        };
+        let saved = |entity: Entity| drawable.iter().any(|(e, _)| *e == entity);
+        let parents: Vec<Entity> = drawable
+            .iter()
+            .filter_map(|(entity, _)| world.get::<Parent>(*entity).map(|parent| parent.0))
+            .collect();
+
+        for (entity, (transform, renderable)) in &drawable {
+            let entity = *entity;
+            let name = if parents.contains(&entity) {
+                parent_name(entity)
+            } else {
+                object_name(world, entity)
+            };
+            let parent = match world.get::<Parent>(entity) {
+                Some(Parent(parent)) if saved(*parent) => Some(parent_name(*parent)),
+                Some(Parent(parent)) => {
+                    eprintln!(
+                        "Parent {:?} of {:?} has no mesh and is not saved",
+                        parent, name
+                    );
+                    None
+                }
+                None => None,
+            };
+
+            let object = ObjectDesc {
+                mesh: capture.mesh(&renderable.mesh),
+                material: Some(capture.material(&renderable.material)),
+                texture: None,
+                transform: TransformDesc::from_transform(transform),
+                color1: one(),
+                color2: one(),
+                tint: renderable.tint.into(),
+                rotation: world.get::<RotationAnimation>(entity).map(|animation| {
+                    RotationAnimDesc {
+                        axis: animation.axis.into(),
+                        speed: animation.speed,
+                    }
+                }),
+                color_animation: world
+                    .get::<ColorAnimation>(entity)
+                    .map(|animation| animation.speed),
+                animation: world
+                    .get::<Animator>(entity)
+                    .map(AnimatorDesc::from_animator),
+                ground: false,
+                alpha_cutout: false,
+                opacity: None,
+                name: Some(name),
+                parent,
+            };
+            capture.desc.objects.push(object);
+        }
+
+        capture.desc
+    }
+
+    /// Pobiera siatki i tekstury z cache, a potem tworzy encje sceny
+    /// (bez `WorldTransform` – liczy je `SceneGraph::update`)
+    pub fn build(&self, assets: &mut AssetManager) -> World {
+        let meshes: BTreeMap<&str, Rc<Mesh>> = self
+            .meshes
+            .iter()
+            .map(|(name, source)| {
+                let mesh = match source {
+                    MeshSource::Obj(path) => assets.mesh(path),
+                    MeshSource::Quad => assets.quad(),
+                    MeshSource::ObjPart { path, part } => {
+                        let parts = assets.model(path);
+                        match parts.into_iter().find(|p| p.name == *part) {
+                            Some(found) => found.mesh,
+                            None => {
+                                eprintln!("OBJ part {:?} not found in {:?}", part, path);
+                                assets.placeholder_mesh()
+                            }
+                        }
+                    }
+                };
+                (name.as_str(), mesh)
+            })
+            .collect();
+
+        let textures: BTreeMap<&str, Rc<Texture>> = self
+            .textures
+            .iter()
+            .map(|(name, desc)| {
+                let wrap = if desc.mirrored_repeat {
+                    Wrap::MirroredRepeat
+                } else {
+                    Wrap::Repeat
+                };
+                (name.as_str(), assets.texture_with_wrap(&desc.path, wrap))
+            })
+            .collect();
+
+        let basic = assets.program(BASIC_VERT, BASIC_FRAG);
+
+        let materials: BTreeMap<&str, Rc<Material>> = self
+            .materials
+            .iter()
+            .map(|(name, desc)| {
+                let material = desc.build(name, &textures, assets);
+                (name.as_str(), Rc::new(material))
+            })
+            .collect();
+
+        let mut world = World::new();
+        let mut rng = rand::thread_rng();
+
+        for scatter in &self.scatter {
+            let desc = Rc::new(scatter.clone());
+            let mesh = lookup(&meshes, &scatter.mesh, "mesh", || assets.placeholder_mesh());
+            // jeden materiał dla wszystkich egzemplarzy
+            let material = material_for(
+                &scatter.material,
+                &materials,
+                &textures,
+                &basic,
+                assets,
+                InlineMaterial {
+                    name: &scatter.mesh,
+                    texture: &scatter.texture,
+                    color1: scatter.color1,
+                    color2: scatter.color2,
+                    ground: false,
+                    alpha_cutout: scatter.alpha_cutout,
+                    opacity: None,
+                },
+            );
+
+            for (transform, tint) in scatter_placements(scatter, &mut rng) {
+                let renderable = Renderable::new(mesh.clone(), material.clone()).with_tint(tint);
+                world
+                    .spawn()
+                    .with(Name(scatter.mesh.clone()))
+                    .with(transform)
+                    .with(renderable)
+                    .with(Scattered {
+                        scatter: desc.clone(),
+                        placed: transform,
+                        tint,
+                    });
+            }
+        }
+
+        let mut named: BTreeMap<&str, Entity> = BTreeMap::new();
+        let mut links: Vec<(Entity, &str)> = Vec::new();
+
+        for desc in &self.objects {
+            let material = material_for(
+                &desc.material,
+                &materials,
+                &textures,
+                &basic,
+                assets,
+                InlineMaterial {
+                    name: &desc.mesh,
+                    texture: &desc.texture,
+                    color1: desc.color1,
+                    color2: desc.color2,
+                    ground: desc.ground,
+                    alpha_cutout: desc.alpha_cutout,
+                    opacity: desc.opacity,
+                },
+            );
+            let mesh = lookup(&meshes, &desc.mesh, "mesh", || assets.placeholder_mesh());
+            let name = desc.name.as_deref().unwrap_or(&desc.mesh);
+
+            let mut entity = world
+                .spawn()
+                .with(Name(name.to_string()))
+                .with(desc.transform.transform())
+                .with(Renderable::new(mesh, material).with_tint(vec3(desc.tint)));
+            if let Some(rotation) = &desc.rotation {
+                entity = entity.with(RotationAnimation {
+                    axis: vec3(rotation.axis),
+                    speed: rotation.speed,
+                });
+            }
+            if let Some(speed) = desc.color_animation {
+                entity = entity.with(ColorAnimation { speed });
+            }
+            if let Some(animation) = &desc.animation {
+                entity = entity.with(animation.build());
+            }
+
+            let entity = entity.id();
+            if let Some(name) = &desc.name {
+                named.insert(name, entity);
+            }
+            if let Some(parent) = &desc.parent {
+                links.push((entity, parent));
+            }
+        }
+
+        // rodzice są podpinani na końcu, więc mogą stać w pliku po dzieciach
+        for (child, parent) in links {
+            let Some(&parent_entity) = named.get(parent) else {
+                eprintln!("Parent object {:?} not found in scene", parent);
+                continue;
+            };
+            if let Err(e) = scene_graph::attach(&mut world, child, parent_entity) {
+                eprintln!("Parent object {:?}: {}", parent, e);
+            }
+        }
+
+        for model in &self.models {
+            let parts = assets.model(&model.path);
+            for (name, renderable) in Renderable::from_obj_parts(parts, assets) {
+                world
+                    .spawn()
+                    .with(name)
+                    .with(model.transform.transform())
+                    .with(renderable);
+            }
+        }
+
+        world
+    }
+}
+
+/// Transformacja i odcień każdego quada rozsiewu: zapisane `instances`
+/// albo losowanie według `count`, `area_*` i `scale`
+fn scatter_placements(scatter: &ScatterDesc, rng: &mut impl Rng) -> Vec<(Transform, Vector3<f32>)> {
+    if !scatter.instances.is_empty() {
+        return scatter
+            .instances
+            .iter()
+            .map(|instance| (instance.transform.transform(), vec3(instance.tint)))
+            .collect();
+    }
+
+    let mut placements = Vec::new();
+    for _ in 0..scatter.count {
+        let x = random_in(rng, scatter.area_min[0], scatter.area_max[0]);
+        let z = random_in(rng, scatter.area_min[1], scatter.area_max[1]);
+        let scale = random_in(rng, scatter.scale[0], scatter.scale[1]);
+        let rotation = rng.gen_range(0.0, std::f32::consts::TAU);
+        let variation = scatter.tint_variation;
+        let brightness = 1.0 + random_in(rng, -variation, variation);
+
+        let mut angles = vec![rotation];
+        if scatter.crossed {
+            angles.push(rotation + std::f32::consts::FRAC_PI_2);
+        }
+
+        for angle in angles {
+            let transform = Transform::from_translation(Vector3::new(x, 0.0, z))
+                .with_rotation(Quaternion::from_angle_y(Rad(angle)))
+                .with_scale(Vector3::new(scale, scale, scale));
+            placements.push((transform, Vector3::new(brightness, brightness, brightness)));
+        }
+    }
+    placements
+}
+
+/// Nazwa obiektu do zapisu – z komponentu `Name` albo identyfikator encji
+fn object_name(world: &World, entity: Entity) -> String {
+    world
+        .get::<Name>(entity)
+        .map_or_else(|| format!("{:?}", entity), |name| name.0.clone())
+}
+
+/// Stan `SceneDesc::capture`: zasoby współdzielone przez obiekty trafiają
+/// do opisu raz, pod unikalną nazwą
+struct Capture<'a> {
+    assets: &'a AssetManager,
+    desc: SceneDesc,
+    /// Adres zasobu (`Rc::as_ptr`) -> nazwa w opisie
+    names: HashMap<usize, String>,
+}
+
+impl Capture<'_> {
+    fn mesh(&mut self, mesh: &Rc<Mesh>) -> String {
+        let key = Rc::as_ptr(mesh) as usize;
+        if let Some(name) = self.names.get(&key) {
+            return name.clone();
+        }
+
+        let (base, source) = if let Some(path) = self.assets.mesh_path(mesh) {
+            let source = if path == Path::new(assets::QUAD_KEY) {
+                MeshSource::Quad
+            } else {
+                MeshSource::Obj(path.display().to_string())
+            };
+            (file_name(path), source)
+        } else if let Some((path, part)) = self.assets.model_part(mesh) {
+            let source = MeshSource::ObjPart {
+                path: path.display().to_string(),
+                part: part.to_string(),
+            };
+            (part.to_string(), source)
+        } else {
+            eprintln!("Mesh is not in the asset cache, saving a placeholder");
+            (
+                "placeholder".to_string(),
+                MeshSource::Obj("<placeholder>".to_string()),
+            )
+        };
+
+        let name = unique_name(&self.desc.meshes, &base);
+        self.desc.meshes.insert(name.clone(), source);
+        self.names.insert(key, name.clone());
+        name
+    }
+
+    fn texture(&mut self, texture: &Rc<Texture>) -> String {
+        let key = Rc::as_ptr(texture) as usize;
+        if let Some(name) = self.names.get(&key) {
+            return name.clone();
+        }
+
+        // ścieżka z wczytania, nie z cache – zastępcze szachownice różnych
+        // plików muszą wrócić pod swoje ścieżki
+        let path = texture.source().unwrap_or_else(|| {
+            eprintln!("Texture was not loaded from a file, saving the checkerboard");
+            Path::new("<checkerboard>")
+        });
+        let name = unique_name(&self.desc.textures, &file_name(path));
+        let desc = TextureDesc {
+            path: path.display().to_string(),
+            mirrored_repeat: texture.wrap() == Wrap::MirroredRepeat,
+        };
+        self.desc.textures.insert(name.clone(), desc);
+        self.names.insert(key, name.clone());
+        name
+    }
+
+    fn material(&mut self, material: &Rc<Material>) -> String {
+        let key = Rc::as_ptr(material) as usize;
+        if let Some(name) = self.names.get(&key) {
+            return name.clone();
+        }
+
+        let (vert, frag) = match self.assets.program_paths(&material.program) {
+            Some((vert, frag)) => (vert.display().to_string(), frag.display().to_string()),
+            None => {
+                eprintln!(
+                    "Program of material {:?} is not in the asset cache, saving the basic one",
+                    material.name
+                );
+                (basic_vert(), basic_frag())
+            }
+        };
+        let textures = material
+            .textures
+            .iter()
+            .map(|(slot, texture)| (slot.clone(), self.texture(texture)))
+            .collect();
+        let params = material
+            .params
+            .iter()
+            .map(|(param, value)| (param.clone(), ParamDesc::from(*value)))
+            .collect();
+
+        let desc = MaterialDesc {
+            vert,
+            frag,
+            textures,
+            params,
+            alpha_cutout: material.flags.mode == RenderMode::Cutout,
+            blended: material.flags.mode == RenderMode::Blended,
+            cast_shadows: material.flags.cast_shadows,
+        };
+        let name = unique_name(&self.desc.materials, &material.name);
+        self.desc.materials.insert(name.clone(), desc);
+        self.names.insert(key, name.clone());
+        name
+    }
+}
+
+/// Nazwa pliku bez rozszerzenia; klucze wbudowanych zasobów bez nawiasów
+fn file_name(path: &Path) -> String {
+    let stem = path.file_stem().unwrap_or(path.as_os_str());
+    stem.to_string_lossy().trim_matches(['<', '>']).to_string()
+}
+
+/// `base`, a gdy jest już zajęta – `base_2`, `base_3`, ...
+fn unique_name<T>(map: &BTreeMap<String, T>, base: &str) -> String {
+    let mut name = base.to_string();
+    let mut n = 1;
+    while map.contains_key(&name) {
+        n += 1;
+        name = format!("{}_{}", base, n);
+    }
+    name
+}
+
+/// Losowa wartość z [`low`, `high`); pusty zakres daje `low`
+/// (`gen_range` z rand 0.5 panikuje, gdy `low >= high`)
+fn random_in(rng: &mut impl Rng, low: f32, high: f32) -> f32 {
+    if low < high {
+        rng.gen_range(low, high)
+    } else {
+        low
+    }
+}
+
+/// Materiał z nazwy albo z pól opisu obiektu
+fn material_for(
+    name: &Option<String>,
+    materials: &BTreeMap<&str, Rc<Material>>,
+    textures: &BTreeMap<&str, Rc<Texture>>,
+    basic: &Rc<Program>,
+    assets: &mut AssetManager,
+    inline: InlineMaterial,
+) -> Rc<Material> {
+    match name {
+        Some(name) => lookup(materials, name, "material", || {
+            let white = Vector3::new(1.0, 1.0, 1.0);
+            Rc::new(Material::basic(
+                name,
+                basic.clone(),
+                white,
+                white,
+                assets.checkerboard(),
+            ))
+        }),
+        None => Rc::new(inline.build(basic, textures, assets)),
+    }
+}
+
+impl MaterialDesc {
+    fn build(
+        &self,
+        name: &str,
+        textures: &BTreeMap<&str, Rc<Texture>>,
+        assets: &mut AssetManager,
+    ) -> Material {
+        let program = assets.program(&self.vert, &self.frag);
+
+        // domyślne parametry basic.frag/pbr.frag, nadpisywane przez opis
+        let mut material = if self.vert == BASIC_VERT && self.frag == BASIC_FRAG {
+            let white = Vector3::new(1.0, 1.0, 1.0);
+            Material::basic(name, program, white, white, assets.white())
+        } else if self.vert == PBR_VERT && self.frag == PBR_FRAG {
+            Material::pbr(name, program, assets.white(), assets.flat_normal())
+        } else {
+            Material::new(name, program)
+        };
+
+        for (slot, texture) in &self.textures {
+            let texture = lookup(textures, texture, "texture", || assets.checkerboard());
+            material = material.with_texture(slot, texture);
+        }
+        for (param, value) in &self.params {
+            material = material.with_param(param, (*value).into());
+        }
+
+        material.with_flags(RenderFlags {
+            mode: render_mode(self.alpha_cutout, self.blended),
+            cast_shadows: self.cast_shadows,
+        })
+    }
+}
+
+/// Pola obiektu/rozsiewu opisujące wygląd bez nazwanego materiału
+struct InlineMaterial<'a> {
+    name: &'a str,
+    texture: &'a Option<String>,
+    color1: [f32; 3],
+    color2: [f32; 3],
+    ground: bool,
+    alpha_cutout: bool,
+    opacity: Option<f32>,
+}
+
+impl InlineMaterial<'_> {
+    fn build(
+        &self,
+        program: &Rc<Program>,
+        textures: &BTreeMap<&str, Rc<Texture>>,
+        assets: &mut AssetManager,
+    ) -> Material {
+        let texture = match self.texture {
+            Some(name) => lookup(textures, name, "texture", || assets.checkerboard()),
+            None => assets.white(),
+        };
+
+        let mut material = Material::basic(
+            self.name,
+            program.clone(),
+            vec3(self.color1),
+            vec3(self.color2),
+            texture,
+        )
+        .with_flags(RenderFlags {
+            mode: render_mode(self.alpha_cutout, self.opacity.is_some_and(|o| o < 1.0)),
+            ..RenderFlags::default()
+        });
+
+        if let Some(opacity) = self.opacity {
+            material = material.with_param("u_opacity", MaterialParam::Float(opacity));
+        }
+
+        if self.ground {
+            material = material
+                .with_param(
+                    "u_gradient_normal",
+                    MaterialParam::Vec3(Vector3::new(0.0, 0.0, 0.0)),
+                )
+                .with_param(
+                    "u_gradient_world",
+                    MaterialParam::Vec3(Vector3::new(0.1, 0.0, 0.0)),
+                );
+        }
+        material
+    }
+}
+
+fn render_mode(alpha_cutout: bool, blended: bool) -> RenderMode {
+    if blended {
+        RenderMode::Blended
+    } else if alpha_cutout {
+        RenderMode::Cutout
+    } else {
+        RenderMode::Opaque
+    }
+}
+
+fn lookup<T>(
+    map: &BTreeMap<&str, Rc<T>>,
+    name: &str,
+    kind: &str,
+    fallback: impl FnOnce() -> Rc<T>,
+) -> Rc<T> {
+    map.get(name).cloned().unwrap_or_else(|| {
+        eprintln!(
+            "Scene references unknown {} '{}', using placeholder",
+            kind, name
+        );
+        fallback()
+    })
+}
@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use cgmath::{Matrix4, One, Quaternion, Vector3};
+
+use crate::components::RotationAnimation;
+use crate::ecs::{Entity, World};
+
+/// Lokalna transformacja węzła względem rodzica (T * R * S)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub translation: Vector3<f32>,
+    pub rotation: Quaternion<f32>,
+    pub scale: Vector3<f32>,
+}
+
+impl Transform {
+    pub fn from_translation(translation: Vector3<f32>) -> Self {
+        Transform {
+            translation,
+            ..Transform::default()
+        }
+    }
+
+    pub fn with_rotation(mut self, rotation: Quaternion<f32>) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn with_scale(mut self, scale: Vector3<f32>) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn matrix(&self) -> Matrix4<f32> {
+        Matrix4::from_translation(self.translation)
+            * Matrix4::from(self.rotation)
+            * Matrix4::from_nonuniform_scale(self.scale.x, self.scale.y, self.scale.z)
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform {
+            translation: Vector3::new(0.0, 0.0, 0.0),
+            rotation: Quaternion::one(),
+            scale: Vector3::new(1.0, 1.0, 1.0),
+        }
+    }
+}
+
+/// Rodzic encji w grafie sceny – jej `Transform` jest względem niego
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Parent(pub Entity);
+
+/// Macierz świata policzona przez `SceneGraph::update`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WorldTransform(pub Matrix4<f32>);
+
+#[derive(Debug)]
+pub enum SceneGraphError {
+    MissingEntity(Entity),
+    /// Rodzic jest potomkiem dziecka – powstałby cykl
+    Cycle {
+        child: Entity,
+        parent: Entity,
+    },
+}
+
+impl fmt::Display for SceneGraphError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SceneGraphError::MissingEntity(entity) => {
+                write!(f, "entity {:?} does not exist", entity)
+            }
+            SceneGraphError::Cycle { child, parent } => write!(
+                f,
+                "cannot attach {:?} to {:?}: it is one of its descendants",
+                child, parent
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SceneGraphError {}
+
+/// Podpina `child` pod `parent`, odrzucając połączenia tworzące cykl
+pub fn attach(world: &mut World, child: Entity, parent: Entity) -> Result<(), SceneGraphError> {
+    for entity in [child, parent] {
+        if !world.contains(entity) {
+            return Err(SceneGraphError::MissingEntity(entity));
+        }
+    }
+    let mut ancestors = std::iter::successors(Some(parent), |&node| {
+        world.get::<Parent>(node).map(|parent| parent.0)
+    });
+    if ancestors.any(|node| node == child) {
+        return Err(SceneGraphError::Cycle { child, parent });
+    }
+
+    world.insert(child, Parent(parent));
+    Ok(())
+}
+
+/// Odpina encję od rodzica; `Transform` zostaje, więc jest odtąd względem świata
+pub fn detach(world: &mut World, child: Entity) -> Option<Entity> {
+    world.remove::<Parent>(child).map(|parent| parent.0)
+}
+
+/// Usuwa encję ze świata; jej dzieci zostają odpięte i stają się korzeniami
+pub fn despawn(world: &mut World, entity: Entity) -> bool {
+    let children: Vec<Entity> = world
+        .query::<&Parent>()
+        .filter(|(_, parent)| parent.0 == entity)
+        .map(|(child, _)| child)
+        .collect();
+    for child in children {
+        detach(world, child);
+    }
+    world.despawn(entity)
+}
+
+/// Hierarchia transformacji nad encjami z `Transform`. Macierze świata są
+/// trzymane w komponentach `WorldTransform` i liczone w `update` tylko dla
+/// encji zmienionych, animowanych albo z takim przodkiem.
+#[derive(Default)]
+pub struct SceneGraph {
+    /// Kolejność aktualizacji – rodzic zawsze przed swoimi dziećmi
+    order: Vec<Entity>,
+    /// `World::structure_version` przy ostatnim budowaniu kolejności
+    version: u64,
+}
+
+impl SceneGraph {
+    pub fn new() -> Self {
+        SceneGraph::default()
+    }
+
+    /// Przelicza macierze świata w chwili `time`. Animacja obrotu działa
+    /// w przestrzeni lokalnej encji i obraca razem z nią wszystkie dzieci.
+    pub fn update(&mut self, world: &mut World, time: f32) {
+        let rebuild = world.structure_version() != self.version || world.any_changed::<Parent>();
+        if rebuild {
+            self.rebuild_order(world);
+        }
+
+        let mut changed = vec![false; world.len()];
+        for &entity in &self.order {
+            let Some(&transform) = world.get::<Transform>(entity) else {
+                continue;
+            };
+            let parent = world.get::<Parent>(entity).map(|parent| parent.0);
+            let animation = world.get::<RotationAnimation>(entity).copied();
+            let parent_changed = parent.is_some_and(|parent| changed[parent.index()]);
+
+            let dirty = rebuild
+                || parent_changed
+                || animation.is_some()
+                || world.is_changed::<Transform>(entity);
+            if !dirty {
+                continue;
+            }
+
+            let mut local = transform.matrix();
+            if let Some(animation) = animation {
+                local = local * animation.matrix(time);
+            }
+            let parent_world = parent.and_then(|parent| world.get::<WorldTransform>(parent));
+            let matrix = match parent_world {
+                Some(parent_world) => parent_world.0 * local,
+                None => local,
+            };
+            world.insert(entity, WorldTransform(matrix));
+            changed[entity.index()] = true;
+        }
+
+        world.clear_changed::<Transform>();
+        world.clear_changed::<Parent>();
+    }
+
+    /// Przejście w głąb od korzeni; encje w cyklu (wstawione z pominięciem
+    /// `attach`) nie trafiają do kolejności i nie dostają macierzy świata
+    fn rebuild_order(&mut self, world: &World) {
+        let mut children: HashMap<Entity, Vec<Entity>> = HashMap::new();
+        let mut stack = Vec::new();
+        for (entity, (_, parent)) in world.query::<(&Transform, Option<&Parent>)>() {
+            match parent {
+                Some(parent) if world.has::<Transform>(parent.0) => {
+                    children.entry(parent.0).or_default().push(entity)
+                }
+                _ => stack.push(entity),
+            }
+        }
+        stack.reverse();
+
+        self.order.clear();
+        while let Some(entity) = stack.pop() {
+            self.order.push(entity);
+            if let Some(children) = children.get(&entity) {
+                stack.extend(children.iter().rev());
+            }
+        }
+        self.version = world.structure_version();
+    }
+}
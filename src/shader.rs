@@ -1,26 +1,268 @@
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
 use std::ffi::CString;
+use std::fmt;
 use std::fs;
-use std::path::Path;
+use std::io;
+use std::path::{Path, PathBuf};
 use std::ptr;
+use std::time::SystemTime;
 
 use cgmath::Matrix4;
 use cgmath::prelude::*;
 
 pub struct Program {
-    pub id: u32,
+    id: Cell<u32>,
+    sources: Option<SourceFiles>,
+    reflection: RefCell<Reflection>,
+    /// Nazwy uniformów, o których już ostrzegaliśmy
+    warned: RefCell<HashSet<String>>,
 }
 
+/// Aktywny uniform lub atrybut programu odczytany po linkowaniu
+#[derive(Debug, Clone)]
+pub struct ShaderVariable {
+    pub name: String,
+    pub location: i32,
+    /// Typ GL, np. `gl::FLOAT_VEC3`
+    pub ty: u32,
+    /// Liczba elementów (dla tablic > 1)
+    pub size: i32,
+}
+
+impl ShaderVariable {
+    pub fn type_name(&self) -> &'static str {
+        type_name(self.ty)
+    }
+}
+
+#[derive(Default)]
+struct Reflection {
+    uniforms: HashMap<String, ShaderVariable>,
+    attributes: Vec<ShaderVariable>,
+}
+
+impl Reflection {
+    fn query(program: u32) -> Reflection {
+        let mut uniforms = HashMap::new();
+        let mut attributes = Vec::new();
+
+        unsafe {
+            let mut count = 0;
+            let mut max_len = 0;
+            gl::GetProgramiv(program, gl::ACTIVE_UNIFORMS, &mut count);
+            gl::GetProgramiv(program, gl::ACTIVE_UNIFORM_MAX_LENGTH, &mut max_len);
+            let mut buffer = vec![0u8; max_len.max(1) as usize];
+
+            for i in 0..count.max(0) as u32 {
+                let (mut len, mut size, mut ty) = (0, 0, 0);
+                gl::GetActiveUniform(
+                    program,
+                    i,
+                    buffer.len() as i32,
+                    &mut len,
+                    &mut size,
+                    &mut ty,
+                    buffer.as_mut_ptr() as *mut i8,
+                );
+                let name = String::from_utf8_lossy(&buffer[..len as usize]).into_owned();
+                let c_name = CString::new(name.as_str()).unwrap();
+                // uniformy z bloków (UBO) mają lokalizację -1
+                let location = gl::GetUniformLocation(program, c_name.as_ptr());
+
+                let variable = ShaderVariable {
+                    name: name.clone(),
+                    location,
+                    ty,
+                    size,
+                };
+                // tablica "u_x[0]" jest dostępna też jako "u_x"
+                if let Some(base) = name.strip_suffix("[0]") {
+                    uniforms.insert(base.to_string(), variable.clone());
+                }
+                uniforms.insert(name, variable);
+            }
+
+            gl::GetProgramiv(program, gl::ACTIVE_ATTRIBUTES, &mut count);
+            gl::GetProgramiv(program, gl::ACTIVE_ATTRIBUTE_MAX_LENGTH, &mut max_len);
+            let mut buffer = vec![0u8; max_len.max(1) as usize];
+
+            for i in 0..count.max(0) as u32 {
+                let (mut len, mut size, mut ty) = (0, 0, 0);
+                gl::GetActiveAttrib(
+                    program,
+                    i,
+                    buffer.len() as i32,
+                    &mut len,
+                    &mut size,
+                    &mut ty,
+                    buffer.as_mut_ptr() as *mut i8,
+                );
+                let name = String::from_utf8_lossy(&buffer[..len as usize]).into_owned();
+                let c_name = CString::new(name.as_str()).unwrap();
+                let location = gl::GetAttribLocation(program, c_name.as_ptr());
+
+                attributes.push(ShaderVariable {
+                    name,
+                    location,
+                    ty,
+                    size,
+                });
+            }
+        }
+
+        attributes.sort_by_key(|a| a.location);
+        Reflection {
+            uniforms,
+            attributes,
+        }
+    }
+}
+
+const FLOAT_TYPES: &[u32] = &[gl::FLOAT];
+const VEC3_TYPES: &[u32] = &[gl::FLOAT_VEC3];
+const MAT4_TYPES: &[u32] = &[gl::FLOAT_MAT4];
+const INT_TYPES: &[u32] = &[
+    gl::INT,
+    gl::BOOL,
+    gl::SAMPLER_2D,
+    gl::SAMPLER_2D_SHADOW,
+    gl::SAMPLER_2D_ARRAY,
+    gl::SAMPLER_CUBE,
+];
+
+fn type_name(ty: u32) -> &'static str {
+    match ty {
+        gl::FLOAT => "float",
+        gl::FLOAT_VEC2 => "vec2",
+        gl::FLOAT_VEC3 => "vec3",
+        gl::FLOAT_VEC4 => "vec4",
+        gl::FLOAT_MAT3 => "mat3",
+        gl::FLOAT_MAT4 => "mat4",
+        gl::INT => "int",
+        gl::BOOL => "bool",
+        gl::SAMPLER_2D => "sampler2D",
+        gl::SAMPLER_2D_SHADOW => "sampler2DShadow",
+        gl::SAMPLER_2D_ARRAY => "sampler2DArray",
+        gl::SAMPLER_CUBE => "samplerCube",
+        _ => "other",
+    }
+}
+
+/// Pliki źródłowe programu i ich czasy modyfikacji (do hot reload)
+struct SourceFiles {
+    vert: PathBuf,
+    frag: PathBuf,
+    modified: Cell<[Option<SystemTime>; 2]>,
+}
+
+impl SourceFiles {
+    fn new(vert: &Path, frag: &Path) -> Self {
+        SourceFiles {
+            vert: vert.to_path_buf(),
+            frag: frag.to_path_buf(),
+            modified: Cell::new([modified_time(vert), modified_time(frag)]),
+        }
+    }
+
+    /// Zwraca true, jeśli któryś plik zmienił się od ostatniego sprawdzenia
+    fn changed(&self) -> bool {
+        let current = [modified_time(&self.vert), modified_time(&self.frag)];
+        if current != self.modified.get() {
+            self.modified.set(current);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Shader zastępczy – jednolity magenta, gdy właściwy program się nie skompiluje
+const ERROR_VERT: &str = r#"
+#version 330 core
+layout(location = 0) in vec3 a_pos;
+layout(location = 3) in mat4 a_model;
+
+uniform mat4 u_view;
+uniform mat4 u_proj;
+
+void main() {
+    gl_Position = u_proj * u_view * a_model * vec4(a_pos, 1.0);
+}
+"#;
+
+const ERROR_FRAG: &str = r#"
+#version 330 core
+out vec4 FragColor;
+
+void main() {
+    FragColor = vec4(1.0, 0.0, 1.0, 1.0);
+}
+"#;
+
+#[derive(Debug)]
+pub enum ShaderError {
+    Io { path: PathBuf, source: io::Error },
+    Compile { stage: &'static str, log: String },
+    Link { log: String },
+}
+
+impl fmt::Display for ShaderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShaderError::Io { path, source } => {
+                write!(f, "failed to read shader {:?}: {}", path, source)
+            }
+            ShaderError::Compile { stage, log } => {
+                write!(f, "{} shader compile error: {}", stage, log)
+            }
+            ShaderError::Link { log } => write!(f, "program linking error: {}", log),
+        }
+    }
+}
+
+impl std::error::Error for ShaderError {}
+
 impl Program {
-    pub fn new(vertex_src: &str, fragment_src: &str) -> Program {
+    pub fn new(vertex_src: &str, fragment_src: &str) -> Result<Program, ShaderError> {
+        Ok(Program::from_id(
+            Self::link(vertex_src, fragment_src)?,
+            None,
+        ))
+    }
+
+    fn from_id(id: u32, sources: Option<SourceFiles>) -> Program {
+        Program {
+            id: Cell::new(id),
+            sources,
+            reflection: RefCell::new(Reflection::query(id)),
+            warned: RefCell::new(HashSet::new()),
+        }
+    }
+
+    fn link(vertex_src: &str, fragment_src: &str) -> Result<u32, ShaderError> {
         unsafe {
-            let vertex_shader = Self::compile_shader(vertex_src, gl::VERTEX_SHADER);
-            let fragment_shader = Self::compile_shader(fragment_src, gl::FRAGMENT_SHADER);
+            let vertex_shader = Self::compile_shader(vertex_src, gl::VERTEX_SHADER)?;
+            let fragment_shader = match Self::compile_shader(fragment_src, gl::FRAGMENT_SHADER) {
+                Ok(shader) => shader,
+                Err(e) => {
+                    gl::DeleteShader(vertex_shader);
+                    return Err(e);
+                }
+            };
 
             let program_id = gl::CreateProgram();
             gl::AttachShader(program_id, vertex_shader);
             gl::AttachShader(program_id, fragment_shader);
             gl::LinkProgram(program_id);
 
+            gl::DeleteShader(vertex_shader);
+            gl::DeleteShader(fragment_shader);
+
             let mut success: i32 = 0;
             gl::GetProgramiv(program_id, gl::LINK_STATUS, &mut success);
             if success == 0 {
@@ -33,31 +275,92 @@ impl Program {
                     ptr::null_mut(),
                     buffer.as_mut_ptr() as *mut i8,
                 );
-                panic!(
-                    "Program linking error: {}",
-                    String::from_utf8_lossy(&buffer)
-                );
+                gl::DeleteProgram(program_id);
+                return Err(ShaderError::Link {
+                    log: String::from_utf8_lossy(&buffer).into_owned(),
+                });
             }
 
-            gl::DeleteShader(vertex_shader);
-            gl::DeleteShader(fragment_shader);
-
-            Program { id: program_id }
+            Ok(program_id)
         }
     }
 
-    pub fn from_files<V: AsRef<Path>, F: AsRef<Path>>(vert_path: V, frag_path: F) -> Program {
-        let vert_source = fs::read_to_string(vert_path).expect("Failed to read vertex shader file");
-        let frag_source =
-            fs::read_to_string(frag_path).expect("Failed to read fragment shader file");
+    pub fn from_files<V: AsRef<Path>, F: AsRef<Path>>(
+        vert_path: V,
+        frag_path: F,
+    ) -> Result<Program, ShaderError> {
+        let sources = SourceFiles::new(vert_path.as_ref(), frag_path.as_ref());
+        let id = Self::link_files(&sources.vert, &sources.frag)?;
+
+        Ok(Program::from_id(id, Some(sources)))
+    }
+
+    fn link_files(vert_path: &Path, frag_path: &Path) -> Result<u32, ShaderError> {
+        let read = |path: &Path| {
+            fs::read_to_string(path).map_err(|source| ShaderError::Io {
+                path: path.to_path_buf(),
+                source,
+            })
+        };
+        let vert_source = read(vert_path)?;
+        let frag_source = read(frag_path)?;
+
+        Self::link(&vert_source, &frag_source)
+    }
+
+    /// Program zastępczy rysujący wszystko na magenta
+    pub fn error() -> Program {
+        Program::new(ERROR_VERT, ERROR_FRAG).expect("Built-in error shader failed to build")
+    }
+
+    /// Program zastępczy, który pilnuje plików źródłowych – po ich
+    /// poprawieniu `reload_if_changed` podmieni go na właściwy shader
+    pub fn error_for_files<V: AsRef<Path>, F: AsRef<Path>>(vert_path: V, frag_path: F) -> Program {
+        let mut program = Program::error();
+        program.sources = Some(SourceFiles::new(vert_path.as_ref(), frag_path.as_ref()));
+        program
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id.get()
+    }
+
+    /// Przebudowuje program, jeśli jego pliki źródłowe się zmieniły.
+    /// Przy błędzie kompilacji/linkowania zostaje poprzednia, działająca wersja.
+    /// Zwraca `None`, gdy nic się nie zmieniło.
+    pub fn reload_if_changed(&self) -> Option<Result<(), ShaderError>> {
+        let sources = self.sources.as_ref()?;
+        if !sources.changed() {
+            return None;
+        }
 
-        Program::new(&vert_source, &frag_source)
+        println!("Reloading shader: {:?} + {:?}", sources.vert, sources.frag);
+        Some(
+            Self::link_files(&sources.vert, &sources.frag).map(|new_id| {
+                let old_id = self.id.replace(new_id);
+                unsafe {
+                    gl::DeleteProgram(old_id);
+                }
+                *self.reflection.borrow_mut() = Reflection::query(new_id);
+                self.warned.borrow_mut().clear();
+            }),
+        )
     }
 
-    fn compile_shader(src: &str, shader_type: u32) -> u32 {
+    fn compile_shader(src: &str, shader_type: u32) -> Result<u32, ShaderError> {
+        let stage = if shader_type == gl::VERTEX_SHADER {
+            "vertex"
+        } else {
+            "fragment"
+        };
+
+        let c_str = CString::new(src.as_bytes()).map_err(|_| ShaderError::Compile {
+            stage,
+            log: "source contains a NUL byte".to_string(),
+        })?;
+
         unsafe {
             let shader = gl::CreateShader(shader_type);
-            let c_str = CString::new(src.as_bytes()).unwrap();
             gl::ShaderSource(shader, 1, &c_str.as_ptr(), std::ptr::null());
             gl::CompileShader(shader);
 
@@ -73,61 +376,167 @@ impl Program {
                     std::ptr::null_mut(),
                     buffer.as_mut_ptr() as *mut i8,
                 );
-                panic!("Shader compile error: {}", String::from_utf8_lossy(&buffer));
+                gl::DeleteShader(shader);
+                return Err(ShaderError::Compile {
+                    stage,
+                    log: String::from_utf8_lossy(&buffer).into_owned(),
+                });
             }
 
-            shader
+            Ok(shader)
         }
     }
 
     pub fn use_program(&self) {
         unsafe {
-            gl::UseProgram(self.id);
+            gl::UseProgram(self.id());
         }
     }
 
-    pub fn get_uniform_location(&self, name: &str) -> i32 {
-        let c_name = CString::new(name).unwrap();
-        unsafe { gl::GetUniformLocation(self.id, c_name.as_ptr()) }
+    /// Aktywne uniformy posortowane po nazwie
+    pub fn uniforms(&self) -> Vec<ShaderVariable> {
+        let reflection = self.reflection.borrow();
+        let mut list: Vec<ShaderVariable> = reflection
+            .uniforms
+            .iter()
+            .filter(|(key, variable)| **key == variable.name)
+            .map(|(_, variable)| variable.clone())
+            .collect();
+        list.sort_by(|a, b| a.name.cmp(&b.name));
+        list
+    }
+
+    pub fn attributes(&self) -> Vec<ShaderVariable> {
+        self.reflection.borrow().attributes.clone()
+    }
+
+    /// Lokalizacja i typ uniformu; elementy tablic spoza odczytu
+    /// (np. "u_x[3]") są doczytywane i dopisywane do cache
+    fn lookup_uniform(&self, name: &str) -> Option<(i32, u32)> {
+        if let Some(variable) = self.reflection.borrow().uniforms.get(name) {
+            return Some((variable.location, variable.ty));
+        }
+
+        let (base, _) = name.strip_suffix(']')?.split_once('[')?;
+        let ty = self
+            .reflection
+            .borrow()
+            .uniforms
+            .get(&format!("{}[0]", base))?
+            .ty;
+
+        let c_name = CString::new(name).ok()?;
+        let location = unsafe { gl::GetUniformLocation(self.id(), c_name.as_ptr()) };
+        if location < 0 {
+            return None;
+        }
+
+        self.reflection.borrow_mut().uniforms.insert(
+            name.to_string(),
+            ShaderVariable {
+                name: name.to_string(),
+                location,
+                ty,
+                size: 1,
+            },
+        );
+        Some((location, ty))
+    }
+
+    fn warn_once(&self, name: &str, message: String) {
+        if self.warned.borrow_mut().insert(name.to_string()) {
+            eprintln!("Warning: {}", message);
+        }
+    }
+
+    /// Lokalizacja uniformu do ustawienia; ostrzega raz, gdy uniform
+    /// nie istnieje albo ma inny typ niż oczekuje setter
+    fn checked_location(&self, name: &str, setter: &str, accepts: &[u32]) -> Option<i32> {
+        match self.lookup_uniform(name) {
+            None => {
+                self.warn_once(
+                    name,
+                    format!("uniform '{}' does not exist in program {}", name, self.id()),
+                );
+                None
+            }
+            Some((_, ty)) if !accepts.contains(&ty) => {
+                self.warn_once(
+                    name,
+                    format!(
+                        "uniform '{}' is {} but was set with {}",
+                        name,
+                        type_name(ty),
+                        setter
+                    ),
+                );
+                None
+            }
+            Some((location, _)) if location < 0 => None,
+            Some((location, _)) => Some(location),
+        }
     }
 
     pub fn set_mat4(&self, name: &str, mat: &Matrix4<f32>) {
-        let loc = self.get_uniform_location(name);
-        if loc < 0 {
+        let Some(loc) = self.checked_location(name, "set_mat4", MAT4_TYPES) else {
             return;
-        }
+        };
         unsafe {
             gl::UniformMatrix4fv(loc, 1, gl::FALSE, mat.as_ptr());
         }
     }
 
     pub fn set_vec3(&self, name: &str, v: &cgmath::Vector3<f32>) {
-        let loc = self.get_uniform_location(name);
-        if loc < 0 {
+        let Some(loc) = self.checked_location(name, "set_vec3", VEC3_TYPES) else {
             return;
-        }
+        };
         unsafe {
             gl::Uniform3f(loc, v.x, v.y, v.z);
         }
     }
 
     pub fn set_float(&self, name: &str, val: f32) {
-        let loc = self.get_uniform_location(name);
-        if loc < 0 {
+        let Some(loc) = self.checked_location(name, "set_float", FLOAT_TYPES) else {
             return;
-        }
+        };
         unsafe {
             gl::Uniform1f(loc, val);
         }
     }
 
     pub fn set_int(&self, name: &str, val: i32) {
-        let loc = self.get_uniform_location(name);
-        if loc < 0 {
+        let Some(loc) = self.checked_location(name, "set_int", INT_TYPES) else {
             return;
-        }
+        };
         unsafe {
             gl::Uniform1i(loc, val);
         }
     }
+
+    /// Czy program ma aktywny uniform o tej nazwie (bez ostrzeżenia)
+    pub fn has_uniform(&self, name: &str) -> bool {
+        self.lookup_uniform(name).is_some()
+    }
+
+    /// Przypisuje blok uniformów (UBO) do punktu wiązania.
+    /// Zwraca false, gdy program nie ma takiego bloku (np. shader zastępczy).
+    pub fn bind_uniform_block(&self, name: &str, binding: u32) -> bool {
+        let c_name = CString::new(name).unwrap();
+        unsafe {
+            let index = gl::GetUniformBlockIndex(self.id(), c_name.as_ptr());
+            if index == gl::INVALID_INDEX {
+                return false;
+            }
+            gl::UniformBlockBinding(self.id(), index, binding);
+        }
+        true
+    }
+}
+
+impl Drop for Program {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteProgram(self.id());
+        }
+    }
 }
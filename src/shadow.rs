@@ -0,0 +1,163 @@
+use cgmath::{EuclideanSpace, InnerSpace, Matrix4, Point3, SquareMatrix, Transform, Vector3};
+
+use crate::framebuffer::FramebufferError;
+
+/// Ustawienia cieni od głównego światła kierunkowego
+#[derive(Debug, Clone, Copy)]
+pub struct ShadowSettings {
+    pub enabled: bool,
+    /// Rozmiar mapy cieni w tekselach (kwadrat)
+    pub resolution: i32,
+    /// Przesunięcie głębokości przeciw "shadow acne"
+    pub bias: f32,
+    /// Połowa boku obszaru pokrytego cieniem (w jednostkach świata)
+    pub extent: f32,
+}
+
+impl Default for ShadowSettings {
+    fn default() -> Self {
+        ShadowSettings {
+            enabled: true,
+            resolution: 2048,
+            bias: 0.002,
+            extent: 12.0,
+        }
+    }
+}
+
+/// Tekstura głębokości renderowana z punktu widzenia światła
+pub struct ShadowMap {
+    fbo: u32,
+    pub depth: u32,
+    pub resolution: i32,
+}
+
+impl ShadowMap {
+    pub fn new(resolution: i32) -> Result<ShadowMap, FramebufferError> {
+        let mut fbo = 0;
+        let mut depth = 0;
+
+        let status = unsafe {
+            gl::GenTextures(1, &mut depth);
+            gl::BindTexture(gl::TEXTURE_2D, depth);
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                gl::DEPTH_COMPONENT24 as i32,
+                resolution,
+                resolution,
+                0,
+                gl::DEPTH_COMPONENT,
+                gl::FLOAT,
+                std::ptr::null(),
+            );
+            // LINEAR + porównanie w sprzęcie daje dodatkowe wygładzenie 2x2
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+            gl::TexParameteri(
+                gl::TEXTURE_2D,
+                gl::TEXTURE_COMPARE_MODE,
+                gl::COMPARE_REF_TO_TEXTURE as i32,
+            );
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_COMPARE_FUNC, gl::LEQUAL as i32);
+            // poza mapą nie ma cienia
+            gl::TexParameteri(
+                gl::TEXTURE_2D,
+                gl::TEXTURE_WRAP_S,
+                gl::CLAMP_TO_BORDER as i32,
+            );
+            gl::TexParameteri(
+                gl::TEXTURE_2D,
+                gl::TEXTURE_WRAP_T,
+                gl::CLAMP_TO_BORDER as i32,
+            );
+            let border = [1.0f32, 1.0, 1.0, 1.0];
+            gl::TexParameterfv(gl::TEXTURE_2D, gl::TEXTURE_BORDER_COLOR, border.as_ptr());
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+
+            gl::GenFramebuffers(1, &mut fbo);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, fbo);
+            gl::FramebufferTexture2D(
+                gl::FRAMEBUFFER,
+                gl::DEPTH_ATTACHMENT,
+                gl::TEXTURE_2D,
+                depth,
+                0,
+            );
+            gl::DrawBuffer(gl::NONE);
+            gl::ReadBuffer(gl::NONE);
+
+            let status = gl::CheckFramebufferStatus(gl::FRAMEBUFFER);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            status
+        };
+
+        let shadow_map = ShadowMap {
+            fbo,
+            depth,
+            resolution,
+        };
+
+        if status != gl::FRAMEBUFFER_COMPLETE {
+            return Err(FramebufferError::Incomplete(status));
+        }
+        Ok(shadow_map)
+    }
+
+    /// Wiąże FBO cieni i czyści głębokość
+    pub fn begin(&self) {
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.fbo);
+            gl::Viewport(0, 0, self.resolution, self.resolution);
+            gl::Clear(gl::DEPTH_BUFFER_BIT);
+        }
+    }
+
+    pub fn bind_texture(&self, unit: u32) {
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0 + unit);
+            gl::BindTexture(gl::TEXTURE_2D, self.depth);
+        }
+    }
+}
+
+impl Drop for ShadowMap {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteFramebuffers(1, &self.fbo);
+            gl::DeleteTextures(1, &self.depth);
+        }
+    }
+}
+
+/// Macierz projekcji * widoku światła kierunkowego: prostopadłościan
+/// o boku 2 * `extent` wokół `center`. Środek jest przyciągany do siatki
+/// tekseli, żeby krawędzie cieni nie migotały przy ruchu kamery.
+pub fn light_space_matrix(
+    direction: Vector3<f32>,
+    center: Point3<f32>,
+    settings: &ShadowSettings,
+) -> Matrix4<f32> {
+    let dir = direction.normalize();
+    let up = if dir.y.abs() > 0.99 {
+        Vector3::unit_z()
+    } else {
+        Vector3::unit_y()
+    };
+    let extent = settings.extent;
+
+    let rotation = Matrix4::look_at_dir(Point3::origin(), dir, up);
+    let texel = 2.0 * extent / settings.resolution as f32;
+    let mut snapped = rotation.transform_point(center);
+    snapped.x = (snapped.x / texel).floor() * texel;
+    snapped.y = (snapped.y / texel).floor() * texel;
+    let center = rotation
+        .invert()
+        .map(|inverse| inverse.transform_point(snapped))
+        .unwrap_or(center);
+
+    let eye = center - dir * extent * 2.0;
+    let view = Matrix4::look_at_dir(eye, dir, up);
+    let proj = cgmath::ortho(-extent, extent, -extent, extent, 0.1, extent * 4.0);
+    proj * view
+}
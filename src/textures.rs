@@ -1,23 +1,92 @@
+use std::fmt;
 use std::os::raw::c_void;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
+use cgmath::Vector2;
 use image::GenericImage;
 
+#[derive(Debug)]
+pub enum TextureError {
+    Image {
+        path: PathBuf,
+        source: image::ImageError,
+    },
+}
+
+impl fmt::Display for TextureError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TextureError::Image { path, source } => {
+                write!(f, "failed to load texture {:?}: {}", path, source)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TextureError {}
+
+/// Zawijanie współrzędnych poza 0..1; ustawiane przy wczytaniu, bo tekstura
+/// z cache bywa współdzielona – `AssetManager` trzyma osobną kopię na tryb
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Wrap {
+    #[default]
+    Repeat,
+    MirroredRepeat,
+}
+
 pub struct Texture {
     pub id: u32,
+    /// Ustawione przez `with_wrap` – zapisywane razem ze sceną
+    wrap: Wrap,
+    /// Ścieżka (albo klucz wbudowany), o którą poproszono `AssetManager` –
+    /// także gdy w jej miejsce trafiła szachownica; zapisywana razem ze sceną
+    source: Option<PathBuf>,
+    /// Kopia kanału alfa w pamięci CPU (picking obiektów `Cutout`);
+    /// `None`, gdy tekstura jest w całości nieprzezroczysta
+    alpha: Option<AlphaMask>,
+}
+
+struct AlphaMask {
+    width: u32,
+    height: u32,
+    values: Vec<u8>,
 }
 
 impl Texture {
-    pub fn from_file<P: AsRef<Path>>(path: P) -> Texture {
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Texture, TextureError> {
         let path_ref = path.as_ref();
         println!("Loading texture: {:?}", path_ref);
 
         // wczytanie obrazu i konwersja na RGBA8
-        let img = image::open(path_ref).expect("Failed to load texture");
+        let img = image::open(path_ref).map_err(|source| TextureError::Image {
+            path: path_ref.to_path_buf(),
+            source,
+        })?;
         let img = img.flipv(); // OpenGL ma (0,0) w lewym dolnym rogu
         let (width, height) = img.dimensions();
         let data = img.to_rgba();
 
+        Ok(Texture::from_rgba(width, height, &data))
+    }
+
+    /// Szachownica magenta/czarny – zastępuje tekstury, których nie udało się wczytać
+    pub fn checkerboard() -> Texture {
+        let size = 64u32;
+        let cell = 8u32;
+        let mut data = Vec::with_capacity((size * size * 4) as usize);
+        for y in 0..size {
+            for x in 0..size {
+                if (x / cell + y / cell).is_multiple_of(2) {
+                    data.extend_from_slice(&[255, 0, 255, 255]);
+                } else {
+                    data.extend_from_slice(&[0, 0, 0, 255]);
+                }
+            }
+        }
+        Texture::from_rgba(size, size, &data)
+    }
+
+    pub fn from_rgba(width: u32, height: u32, data: &[u8]) -> Texture {
         let mut tex_id: u32 = 0;
         unsafe {
             gl::GenTextures(1, &mut tex_id);
@@ -50,7 +119,30 @@ impl Texture {
             gl::BindTexture(gl::TEXTURE_2D, 0);
         }
 
-        Texture { id: tex_id }
+        let alpha = data
+            .chunks_exact(4)
+            .any(|texel| texel[3] < 255)
+            .then(|| AlphaMask {
+                width,
+                height,
+                values: data.chunks_exact(4).map(|texel| texel[3]).collect(),
+            });
+
+        Texture {
+            id: tex_id,
+            wrap: Wrap::Repeat,
+            alpha,
+            source: None,
+        }
+    }
+
+    pub fn with_source<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.source = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    pub fn source(&self) -> Option<&Path> {
+        self.source.as_deref()
     }
 
     pub fn bind(&self, unit: u32) {
@@ -59,20 +151,58 @@ impl Texture {
             gl::BindTexture(gl::TEXTURE_2D, self.id);
         }
     }
-    pub fn set_mirrored_repeat(&self) {
+
+    pub fn with_wrap(mut self, wrap: Wrap) -> Self {
+        self.wrap = wrap;
+        let mode = match wrap {
+            Wrap::Repeat => gl::REPEAT,
+            Wrap::MirroredRepeat => gl::MIRRORED_REPEAT,
+        };
         unsafe {
             gl::BindTexture(gl::TEXTURE_2D, self.id);
-            gl::TexParameteri(
-                gl::TEXTURE_2D,
-                gl::TEXTURE_WRAP_S,
-                gl::MIRRORED_REPEAT as i32,
-            );
-            gl::TexParameteri(
-                gl::TEXTURE_2D,
-                gl::TEXTURE_WRAP_T,
-                gl::MIRRORED_REPEAT as i32,
-            );
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, mode as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, mode as i32);
             gl::BindTexture(gl::TEXTURE_2D, 0);
         }
+        self
+    }
+
+    pub fn wrap(&self) -> Wrap {
+        self.wrap
+    }
+
+    /// Alfa (0..1) w punkcie `uv` – najbliższy teksel, zawijanie jak na GPU
+    pub fn alpha_at(&self, uv: Vector2<f32>) -> f32 {
+        let Some(mask) = &self.alpha else {
+            return 1.0;
+        };
+        let mirrored = self.wrap == Wrap::MirroredRepeat;
+        let x = wrap_texel(uv.x, mask.width, mirrored);
+        let y = wrap_texel(uv.y, mask.height, mirrored);
+        mask.values[(y * mask.width + x) as usize] as f32 / 255.0
+    }
+}
+
+/// Współrzędna tekstury -> indeks teksela dla REPEAT / MIRRORED_REPEAT
+fn wrap_texel(coord: f32, size: u32, mirrored: bool) -> u32 {
+    let size = size as i64;
+    let texel = (coord * size as f32).floor() as i64;
+    if mirrored {
+        let texel = texel.rem_euclid(2 * size);
+        (if texel < size {
+            texel
+        } else {
+            2 * size - 1 - texel
+        }) as u32
+    } else {
+        texel.rem_euclid(size) as u32
+    }
+}
+
+impl Drop for Texture {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteTextures(1, &self.id);
+        }
     }
 }